@@ -0,0 +1,376 @@
+use std::{cmp::Ordering, fmt::Display, ops::Neg, str::FromStr};
+
+use crate::{float, lexer::is_valid_number, number::NumberError};
+
+/// most zeros `checked_add` appends to line up two coefficients, so that
+/// operands like `1e9000000000000000000` and `1` fail instead of taking all
+/// the memory
+const MAX_PADDING: usize = 1_000_000;
+
+/// An exact decimal number of any size: `coefficient × 10^exponent`.
+///
+/// Values are kept normalized, so two decimals are equal exactly when they
+/// have the same value: `1.50`, `1.5` and `15e-1` are all the same number.
+/// Addition, subtraction and multiplication are exact, through the
+/// `checked_*` methods.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    negative: bool,
+
+    /// digits of the coefficient, most significant first, without leading
+    /// or trailing zeros. Empty for zero.
+    digits: Vec<u8>,
+
+    exponent: i64,
+}
+
+impl Decimal {
+    pub fn zero() -> Self {
+        Self {
+            negative: false,
+            digits: vec![],
+            exponent: 0,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.digits.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    /// whether the number has no fractional part
+    pub fn is_integer(&self) -> bool {
+        self.exponent >= 0
+    }
+
+    /// the exact value as an `i128`, failing on fractions and on values
+    /// out of its range instead of truncating
+    pub fn to_i128(&self) -> Result<i128, NumberError> {
+        if !self.is_integer() {
+            return Err(NumberError::NotAnInteger);
+        }
+        if self.digits.len() as i128 + self.exponent as i128 > 39 {
+            return Err(NumberError::OutOfRange);
+        }
+
+        // accumulate as a negative number, which also reaches `i128::MIN`
+        let zeros = std::iter::repeat_n(0, self.exponent as usize);
+        let mut acc: i128 = 0;
+        for d in self.digits.iter().copied().chain(zeros) {
+            acc = acc
+                .checked_mul(10)
+                .and_then(|acc| acc.checked_sub(d as i128))
+                .ok_or(NumberError::OutOfRange)?;
+        }
+        if self.negative {
+            Ok(acc)
+        } else {
+            acc.checked_neg().ok_or(NumberError::OutOfRange)
+        }
+    }
+
+    pub fn to_i64(&self) -> Result<i64, NumberError> {
+        self.to_i128()?
+            .try_into()
+            .map_err(|_| NumberError::OutOfRange)
+    }
+
+    pub fn to_u64(&self) -> Result<u64, NumberError> {
+        self.to_i128()?
+            .try_into()
+            .map_err(|_| NumberError::OutOfRange)
+    }
+
+    /// the nearest `f64`, `OutOfRange` if it is too large for one
+    pub fn to_f64(&self) -> Result<f64, NumberError> {
+        let f = float::parse_f64(&self.scientific()).ok_or(NumberError::Invalid)?;
+        if f.is_infinite() {
+            return Err(NumberError::OutOfRange);
+        }
+        Ok(f)
+    }
+
+    /// the exact sum, `None` if lining up the operands takes more than a
+    /// million zeros or the exponent overflows
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        if self.is_zero() {
+            return Some(other.clone());
+        }
+        if other.is_zero() {
+            return Some(self.clone());
+        }
+
+        let exponent = self.exponent.min(other.exponent);
+        let (a, b) = (self.aligned(exponent)?, other.aligned(exponent)?);
+        let (negative, digits) = if self.negative == other.negative {
+            (self.negative, add_digits(&a, &b))
+        } else {
+            match compare_digits(&a, &b) {
+                Ordering::Equal => return Some(Self::zero()),
+                Ordering::Greater => (self.negative, sub_digits(&a, &b)),
+                Ordering::Less => (other.negative, sub_digits(&b, &a)),
+            }
+        };
+        Self::normalized(negative, digits, exponent)
+    }
+
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        self.checked_add(&-other.clone())
+    }
+
+    pub fn checked_mul(&self, other: &Self) -> Option<Self> {
+        let exponent = self.exponent.checked_add(other.exponent)?;
+        let digits = mul_digits(&self.digits, &other.digits);
+        Self::normalized(self.negative != other.negative, digits, exponent)
+    }
+
+    /// the coefficient with zeros appended to bring it down to `exponent`,
+    /// `None` past `MAX_PADDING` zeros
+    fn aligned(&self, exponent: i64) -> Option<Vec<u8>> {
+        let zeros = usize::try_from(self.exponent.checked_sub(exponent)?).ok()?;
+        if zeros > MAX_PADDING {
+            return None;
+        }
+        let mut digits = Vec::with_capacity(self.digits.len() + zeros);
+        digits.extend_from_slice(&self.digits);
+        digits.resize(self.digits.len() + zeros, 0);
+        Some(digits)
+    }
+
+    /// builds a decimal from any coefficient, dropping its leading and
+    /// trailing zeros. `None` if the exponent overflows.
+    fn normalized(negative: bool, mut digits: Vec<u8>, exponent: i64) -> Option<Self> {
+        let leading = digits.iter().take_while(|&&d| d == 0).count();
+        digits.drain(..leading);
+        if digits.is_empty() {
+            return Some(Self::zero());
+        }
+        let trailing = digits.iter().rev().take_while(|&&d| d == 0).count();
+        digits.truncate(digits.len() - trailing);
+        Some(Self {
+            negative,
+            digits,
+            exponent: exponent.checked_add(trailing as i64)?,
+        })
+    }
+
+    /// position of the decimal point, counted from the first digit
+    fn point(&self) -> i128 {
+        self.digits.len() as i128 + self.exponent as i128
+    }
+
+    /// `d.ddde±x`, whatever the size of the exponent
+    fn scientific(&self) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+        let mut s = String::new();
+        if self.negative {
+            s.push('-');
+        }
+        s.push(char::from(b'0' + self.digits[0]));
+        if self.digits.len() > 1 {
+            s.push('.');
+            s.extend(self.digits[1..].iter().map(|&d| char::from(b'0' + d)));
+        }
+        let exponent = self.point() - 1;
+        s.push_str(&format!(
+            "e{}{}",
+            if exponent < 0 { "-" } else { "+" },
+            exponent.abs()
+        ));
+        s
+    }
+}
+
+impl Default for Decimal {
+    fn default() -> Self {
+        Self::zero()
+    }
+}
+
+/// Accepts the JSON number grammar.
+impl FromStr for Decimal {
+    type Err = NumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !is_valid_number(s) {
+            return Err(NumberError::Invalid);
+        }
+        let (negative, s) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(i) => {
+                let exponent = s[i + 1..].strip_prefix('+').unwrap_or(&s[i + 1..]);
+                let exponent = exponent.parse().map_err(|_| NumberError::OutOfRange)?;
+                (&s[..i], exponent)
+            }
+            None => (s, 0i64),
+        };
+        let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+        let digits = int.bytes().chain(frac.bytes()).map(|b| b - b'0').collect();
+        let exponent = exponent
+            .checked_sub(frac.len() as i64)
+            .ok_or(NumberError::OutOfRange)?;
+        Self::normalized(negative, digits, exponent).ok_or(NumberError::OutOfRange)
+    }
+}
+
+/// Prints the canonical form: plain digits unless that takes more than 20
+/// trailing or 6 leading zeros, like `1234.5`, `0.00012` or `1e+21`.
+impl Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let point = self.point();
+        if self.is_zero() || self.exponent > 20 || point < -5 {
+            return write!(f, "{}", self.scientific());
+        }
+
+        let digits: String = self.digits.iter().map(|&d| char::from(b'0' + d)).collect();
+        let sign = if self.negative { "-" } else { "" };
+        if point <= 0 {
+            let zeros = "0".repeat(point.unsigned_abs() as usize);
+            write!(f, "{sign}0.{zeros}{digits}")
+        } else if self.exponent >= 0 {
+            let zeros = "0".repeat(self.exponent as usize);
+            write!(f, "{sign}{digits}{zeros}")
+        } else {
+            let (int, frac) = digits.split_at(point as usize);
+            write!(f, "{sign}{int}.{frac}")
+        }
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = |d: &Self| match (d.is_zero(), d.negative) {
+            (true, _) => 0,
+            (false, true) => -1,
+            (false, false) => 1,
+        };
+        let by_sign = sign(self).cmp(&sign(other));
+        if by_sign != Ordering::Equal || self.is_zero() {
+            return by_sign;
+        }
+
+        // normalized digits: the point and then the digits decide
+        let magnitude = self
+            .point()
+            .cmp(&other.point())
+            .then_with(|| self.digits.cmp(&other.digits));
+        if self.negative {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl Neg for Decimal {
+    type Output = Self;
+
+    fn neg(mut self) -> Self {
+        self.negative = !self.negative && !self.is_zero();
+        self
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Decimal {
+                fn from(n: $t) -> Self {
+                    let digits = n.unsigned_abs().to_string().bytes().map(|b| b - b'0').collect();
+                    Self::normalized(n < 0, digits, 0).unwrap_or_default()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i8, i16, i32, i64, i128, isize);
+
+macro_rules! impl_from_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Decimal {
+                fn from(n: $t) -> Self {
+                    let digits = n.to_string().bytes().map(|b| b - b'0').collect();
+                    Self::normalized(false, digits, 0).unwrap_or_default()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_unsigned!(u8, u16, u32, u64, u128, usize);
+
+/// compares two coefficients of the same exponent
+fn compare_digits(a: &[u8], b: &[u8]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
+
+fn add_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut sum = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let (mut a, mut b) = (a.iter().rev(), b.iter().rev());
+    let mut carry = 0;
+    loop {
+        let (x, y) = (a.next(), b.next());
+        if x.is_none() && y.is_none() {
+            break;
+        }
+        let d = x.unwrap_or(&0) + y.unwrap_or(&0) + carry;
+        sum.push(d % 10);
+        carry = d / 10;
+    }
+    if carry > 0 {
+        sum.push(carry);
+    }
+    sum.reverse();
+    sum
+}
+
+/// `a - b`, for `a >= b`
+fn sub_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    let mut diff = Vec::with_capacity(a.len());
+    let mut b = b.iter().rev();
+    let mut borrow = 0;
+    for &x in a.iter().rev() {
+        let y = b.next().unwrap_or(&0) + borrow;
+        if x >= y {
+            diff.push(x - y);
+            borrow = 0;
+        } else {
+            diff.push(x + 10 - y);
+            borrow = 1;
+        }
+    }
+    diff.reverse();
+    diff
+}
+
+fn mul_digits(a: &[u8], b: &[u8]) -> Vec<u8> {
+    // least significant first while multiplying
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().rev().enumerate() {
+        let mut carry = 0;
+        for (j, &y) in b.iter().rev().enumerate() {
+            let d = product[i + j] + x as u32 * y as u32 + carry;
+            product[i + j] = d % 10;
+            carry = d / 10;
+        }
+        product[i + b.len()] += carry;
+    }
+    product.iter().rev().map(|&d| d as u8).collect()
+}
@@ -0,0 +1,142 @@
+use std::fmt::Display;
+
+use crate::token::Span;
+
+/// What went wrong while parsing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseErrorKind {
+    /// a token that is not allowed at this point, e.g. `:` inside an array
+    UnexpectedToken(String),
+    /// the input ended before the value was complete
+    UnexpectedEof,
+    /// something other than whitespace after the root value
+    TrailingContent,
+    /// a comma right before a closing `]` or `}`
+    TrailingComma,
+    /// two values without a comma between them
+    MissingComma,
+    /// an object key not followed by `:`
+    MissingColon,
+    /// an unknown or malformed `\` escape inside a string
+    InvalidEscape(String),
+    /// the input ended inside a string
+    UnterminatedString,
+    /// the input ended inside a `/* */` comment
+    UnterminatedComment,
+    /// a raw U+0000 to U+001F character inside a string
+    ControlCharacterInString(char),
+    /// a number that does not follow the JSON grammar
+    InvalidNumber(String),
+    /// a key that appears more than once in the same object. The error span
+    /// points at the repeated key, `first` at its earlier occurrence.
+    DuplicateKey { key: String, first: Span },
+    /// the document is nested deeper than allowed
+    DepthLimitExceeded(usize),
+    /// a JSON Pointer that is not `""` and does not start with `/`, or has
+    /// a `~` not followed by `0` or `1`
+    InvalidPointer(String),
+    /// the document has no value at the given JSON Pointer
+    PointerNotFound(String),
+    /// a character that cannot start any token
+    IllegalCharacter(char),
+    /// a bare word that is not `true`, `false` or `null`
+    InvalidKeyword(String),
+    /// input bytes that are not valid UTF-8
+    InvalidUtf8,
+    /// the `io::Read` the input comes from failed, with its error message
+    Io(String),
+    /// the input is longer than `Limits::max_input_bytes`
+    InputTooLarge(usize),
+    /// a string or key longer than `Limits::max_string_bytes`
+    StringTooLong(usize),
+    /// a number with more digits than `Limits::max_number_digits`
+    NumberTooLong(usize),
+    /// an object with more members than `Limits::max_object_keys`
+    TooManyKeys(usize),
+    /// an array with more elements than `Limits::max_array_elements`
+    TooManyElements(usize),
+    /// a document with more values than `Limits::max_nodes`
+    TooManyNodes(usize),
+}
+
+impl Display for ParseErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedToken(t) => write!(f, "unexpected token {t}"),
+            Self::UnexpectedEof => write!(f, "unexpected end of input"),
+            Self::TrailingContent => write!(f, "unexpected content after the root value"),
+            Self::TrailingComma => write!(f, "trailing comma"),
+            Self::MissingComma => write!(f, "expected comma between values"),
+            Self::MissingColon => write!(f, "expected colon after object key"),
+            Self::InvalidEscape(e) => write!(f, "invalid escape sequence {e}"),
+            Self::UnterminatedString => write!(f, "unterminated string"),
+            Self::UnterminatedComment => write!(f, "unterminated comment"),
+            Self::ControlCharacterInString(c) => {
+                write!(f, "control character {} in string", c.escape_unicode())
+            }
+            Self::InvalidNumber(n) => write!(f, "invalid number {n}"),
+            Self::DuplicateKey { key, first } => {
+                write!(f, "duplicate key \"{key}\", first defined at {first}")
+            }
+            Self::DepthLimitExceeded(max) => write!(f, "nesting deeper than {max} levels"),
+            Self::InvalidPointer(p) => write!(f, "invalid JSON pointer \"{p}\""),
+            Self::PointerNotFound(p) => write!(f, "no value at \"{p}\""),
+            Self::IllegalCharacter(c) => write!(f, "illegal character {c}"),
+            Self::InvalidKeyword(k) => write!(f, "invalid keyword {k}"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8"),
+            Self::Io(e) => write!(f, "read error: {e}"),
+            Self::InputTooLarge(max) => write!(f, "input longer than {max} bytes"),
+            Self::StringTooLong(max) => write!(f, "string longer than {max} bytes"),
+            Self::NumberTooLong(max) => write!(f, "number with more than {max} digits"),
+            Self::TooManyKeys(max) => write!(f, "object with more than {max} keys"),
+            Self::TooManyElements(max) => write!(f, "array with more than {max} elements"),
+            Self::TooManyNodes(max) => write!(f, "document with more than {max} values"),
+        }
+    }
+}
+
+/// One step on the way from the root to a nested value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Location of a value inside the document, printed as a JSON Pointer (RFC 6901).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Path(pub Vec<PathSegment>);
+
+impl Display for Path {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for segment in &self.0 {
+            match segment {
+                PathSegment::Key(k) => write!(f, "/{}", k.replace('~', "~0").replace('/', "~1"))?,
+                PathSegment::Index(i) => write!(f, "/{i}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+
+    /// where in the source text the error was found
+    pub span: Span,
+
+    /// where in the document the error was found
+    pub path: Path,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at {}", self.kind, self.span)?;
+        if !self.path.0.is_empty() {
+            write!(f, " (in {})", self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -0,0 +1,554 @@
+use std::{borrow::Cow, collections::HashMap, fmt::Display, io::Read};
+
+use crate::{
+    error::{ParseError, ParseErrorKind, Path, PathSegment},
+    lexer::{Lexer, LoneSurrogatePolicy},
+    limits::Limits,
+    options::ParserOptions,
+    parser::DuplicateKeyPolicy,
+    token::{Span, SpannedToken, Token},
+};
+
+/// One step of a JSON document, as reported by `EventReader`. Keys, strings
+/// and numbers borrow from the input when they can.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event<'a> {
+    StartObject,
+    /// an object key, always followed by the events of its value
+    Key(Cow<'a, str>),
+    EndObject,
+    StartArray,
+    EndArray,
+    String(Cow<'a, str>),
+    Number(Cow<'a, str>),
+    Bool(bool),
+    Null,
+}
+
+impl Display for Event<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StartObject => write!(f, "{{"),
+            Self::Key(k) => write!(f, "\"{k}\""),
+            Self::EndObject => write!(f, "}}"),
+            Self::StartArray => write!(f, "["),
+            Self::EndArray => write!(f, "]"),
+            Self::String(s) => write!(f, "\"{s}\""),
+            Self::Number(n) => write!(f, "{n}"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Null => write!(f, "null"),
+        }
+    }
+}
+
+/// What the reader expects to find next.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum State {
+    /// any value
+    Value,
+    /// right after `{`: a key or `}`
+    FirstKey,
+    /// after a comma in an object: a key
+    Key,
+    /// right after `[`: a value or `]`
+    FirstElement,
+    /// after a value: a comma or the end of the enclosing container
+    AfterValue,
+    /// the root value is complete
+    Done,
+    /// an error was returned, nothing more will be read
+    Failed,
+}
+
+/// A `PathSegment` that borrows its key like the events do.
+enum Step<'a> {
+    Key(Cow<'a, str>),
+    Index(usize),
+}
+
+/// A container that is open at the current position.
+enum Frame {
+    Object {
+        /// where each key was first seen, kept only to reject duplicates
+        key_spans: Option<HashMap<String, Span>>,
+
+        /// members read so far
+        len: usize,
+    },
+    Array {
+        /// elements read so far
+        len: usize,
+    },
+}
+
+/// Pull parser: reads one JSON value and reports it as a flat sequence of
+/// `Event`s, checking the grammar along the way. Nothing is built, so it is
+/// the base for consumers that never need a `JsonValue` tree.
+pub struct EventReader<'a> {
+    lexer: Lexer<'a>,
+
+    /// token read ahead by `peek_token`, if any
+    peeked: Option<SpannedToken<'a>>,
+
+    /// span of the last token handed out by `next_token`
+    token_span: Span,
+
+    /// span of the token behind the last event
+    event_span: Span,
+
+    state: State,
+
+    /// containers open at the current position, innermost last
+    stack: Vec<Frame>,
+
+    /// keys and indices leading to the value being read. Keys are only
+    /// copied into a `Path` when one is asked for.
+    path: Vec<Step<'a>>,
+
+    /// accept a comma right before `]` or `}`
+    allow_trailing_commas: bool,
+
+    /// fail with `ParseErrorKind::DuplicateKey` when an object repeats a key
+    reject_duplicate_keys: bool,
+
+    limits: Limits,
+
+    /// accept a string, number, boolean or null as the root value
+    allow_scalar_root: bool,
+
+    /// values read so far, for `Limits::max_nodes`
+    nodes: usize,
+
+    /// the string or key of the last event as WTF-8, see `Lexer::wtf8`
+    wtf8: Option<Vec<u8>>,
+}
+
+impl<'a> Iterator for EventReader<'a> {
+    type Item = Result<Event<'a>, ParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_event().transpose()
+    }
+}
+
+impl<'a> EventReader<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::from_lexer(Lexer::new(input))
+    }
+
+    /// reads UTF-8 bytes in place, reporting invalid UTF-8 where it is found
+    pub fn from_slice(input: &'a [u8]) -> Self {
+        Self::from_lexer(Lexer::from_slice(input))
+    }
+
+    /// reads UTF-8 text from `reader` through a fixed-size buffer
+    pub fn from_reader(reader: impl Read + 'a) -> Self {
+        Self::from_lexer(Lexer::from_reader(reader))
+    }
+
+    fn from_lexer(lexer: Lexer<'a>) -> Self {
+        Self {
+            lexer,
+            peeked: None,
+            token_span: Span::default(),
+            event_span: Span::default(),
+            state: State::Value,
+            stack: vec![],
+            path: vec![],
+            allow_trailing_commas: false,
+            reject_duplicate_keys: false,
+            limits: Limits::default(),
+            allow_scalar_root: true,
+            nodes: 0,
+            wtf8: None,
+        }
+    }
+
+    pub fn with_lone_surrogate_policy(mut self, policy: LoneSurrogatePolicy) -> Self {
+        self.lexer = self.lexer.with_lone_surrogate_policy(policy);
+        self
+    }
+
+    /// lenient mode for hand-edited files: accept `[1, 2,]` and `{"a": 1,}`
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.allow_trailing_commas = allow;
+        self
+    }
+
+    pub fn reject_duplicate_keys(mut self, reject: bool) -> Self {
+        self.reject_duplicate_keys = reject;
+        self
+    }
+
+    /// RFC 8259 allows any value at the root. Turning this off only accepts
+    /// an object or an array there, like the older RFC 4627.
+    pub fn allow_scalar_root(mut self, allow: bool) -> Self {
+        self.allow_scalar_root = allow;
+        self
+    }
+
+    /// applies the options that concern the lexer and the reader
+    pub fn with_options(self, options: &ParserOptions) -> Self {
+        let mut reader = self
+            .with_lone_surrogate_policy(options.lone_surrogates)
+            .allow_trailing_commas(options.trailing_commas)
+            .reject_duplicate_keys(options.duplicate_keys == DuplicateKeyPolicy::Error)
+            .allow_scalar_root(options.scalar_root)
+            .with_limits(options.limits);
+        reader.lexer = reader
+            .lexer
+            .allow_comments(options.comments)
+            .allow_nan_infinity(options.nan_infinity)
+            .allow_single_quotes(options.single_quotes);
+        reader
+    }
+
+    /// fails with `ParseErrorKind::DepthLimitExceeded` when containers are
+    /// nested more than `max` levels deep, `limits::DEFAULT_MAX_DEPTH` unless set.
+    /// The reader itself never recurses, so `None` lifts the limit safely
+    /// for events, visitors and streaming.
+    pub fn with_max_depth(mut self, max: Option<usize>) -> Self {
+        self.limits.max_depth = max;
+        self
+    }
+
+    /// replaces all the limits at once, `max_depth` included
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.lexer = self.lexer.with_limits(limits);
+        self.limits = limits;
+        self
+    }
+
+    /// span of the token behind the last event
+    pub fn span(&self) -> Span {
+        self.event_span
+    }
+
+    /// number of containers open at the current position
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// keys and indices leading to the current position
+    pub fn path(&self) -> Path {
+        let segments = self.path.iter().map(|step| match step {
+            Step::Key(key) => PathSegment::Key(key.to_string()),
+            Step::Index(i) => PathSegment::Index(*i),
+        });
+        Path(segments.collect())
+    }
+
+    /// the string or key of the last event as WTF-8, when
+    /// `LoneSurrogatePolicy::Preserve` kept a lone surrogate in it. The
+    /// event has U+FFFD where these bytes have the surrogate.
+    pub fn wtf8(&self) -> Option<&[u8]> {
+        self.wtf8.as_deref()
+    }
+
+    /// reads the next event, or `None` once the root value is complete.
+    /// After an error the reader stops and keeps returning `None`.
+    pub fn next_event(&mut self) -> Result<Option<Event<'a>>, ParseError> {
+        self.wtf8 = None;
+        let event = self.read_event();
+        if event.is_err() {
+            self.state = State::Failed;
+        }
+        event
+    }
+
+    /// reads the next value whole, checking it without building it
+    pub fn skip_value(&mut self) -> Result<(), ParseError> {
+        match self.next_event()? {
+            Some(event) => self.skip_rest(&event),
+            None => Ok(()),
+        }
+    }
+
+    /// finishes skipping a value whose first event was already read
+    pub(crate) fn skip_rest(&mut self, first: &Event) -> Result<(), ParseError> {
+        if matches!(first, Event::StartObject | Event::StartArray) {
+            let depth = self.depth() - 1;
+            while self.depth() > depth {
+                self.next_event()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// checks whether only whitespace is left in the input
+    pub fn is_at_end(&mut self) -> bool {
+        *self.peek_token() == Token::EOF
+    }
+
+    /// fails unless only whitespace is left in the input
+    pub fn expect_end(&mut self) -> Result<(), ParseError> {
+        match self.next_token() {
+            Token::EOF => Ok(()),
+            // not content: the input could not be read to its end
+            token @ (Token::UnterminatedComment | Token::IoError(_) | Token::InputTooLarge(_)) => {
+                Err(self.unexpected(token))
+            }
+            _ => Err(self.error(ParseErrorKind::TrailingContent)),
+        }
+    }
+
+    /// makes the reader ready to read another root value after the
+    /// previous one, for values packed back to back
+    pub(crate) fn restart(&mut self) {
+        if matches!(self.state, State::AfterValue | State::Done) && self.stack.is_empty() {
+            self.state = State::Value;
+        }
+    }
+
+    /// builds an error located at the last token read
+    pub(crate) fn error(&self, kind: ParseErrorKind) -> ParseError {
+        ParseError {
+            kind,
+            span: self.token_span,
+            path: self.path(),
+        }
+    }
+
+    fn read_event(&mut self) -> Result<Option<Event<'a>>, ParseError> {
+        loop {
+            match self.state {
+                State::Done | State::Failed => return Ok(None),
+                State::Value => return self.read_value().map(Some),
+                State::FirstKey => {
+                    if *self.peek_token() == Token::RBrace {
+                        self.next_token();
+                        return Ok(Some(self.close()));
+                    }
+                    self.state = State::Key;
+                }
+                State::Key => return self.read_key().map(Some),
+                State::FirstElement => {
+                    if *self.peek_token() == Token::RSquare {
+                        self.next_token();
+                        return Ok(Some(self.close()));
+                    }
+                    self.start_element();
+                }
+                State::AfterValue => {
+                    let close = match self.stack.last() {
+                        None => {
+                            self.state = State::Done;
+                            return Ok(None);
+                        }
+                        Some(Frame::Object { .. }) => Token::RBrace,
+                        Some(Frame::Array { .. }) => Token::RSquare,
+                    };
+                    if !self.next_separator(close)? {
+                        return Ok(Some(self.close()));
+                    }
+                    match self.stack.last() {
+                        Some(Frame::Object { .. }) => self.state = State::Key,
+                        _ => self.start_element(),
+                    }
+                }
+            }
+        }
+    }
+
+    fn read_value(&mut self) -> Result<Event<'a>, ParseError> {
+        let token = self.next_token();
+        if !matches!(token, Token::EOF) {
+            self.check_limits(&token)?;
+        }
+        if self.stack.is_empty() && !self.allow_scalar_root {
+            if let Token::StringLiteral(_)
+            | Token::NumberLiteral(_)
+            | Token::BooleanLiteral(_)
+            | Token::NullLiteral = token
+            {
+                return Err(self.unexpected(token));
+            }
+        }
+
+        let event = match token {
+            Token::StringLiteral(s) => {
+                self.wtf8 = self.lexer.wtf8().map(<[u8]>::to_vec);
+                Event::String(s)
+            }
+            Token::NumberLiteral(n) => Event::Number(n),
+            Token::BooleanLiteral(b) => Event::Bool(b),
+            Token::NullLiteral => Event::Null,
+            Token::LBrace => {
+                let key_spans = self.reject_duplicate_keys.then(HashMap::new);
+                self.stack.push(Frame::Object { key_spans, len: 0 });
+                self.state = State::FirstKey;
+                self.event_span = self.token_span;
+                return Ok(Event::StartObject);
+            }
+            Token::LSquare => {
+                self.stack.push(Frame::Array { len: 0 });
+                self.state = State::FirstElement;
+                self.event_span = self.token_span;
+                return Ok(Event::StartArray);
+            }
+            token => return Err(self.unexpected(token)),
+        };
+        self.event_span = self.token_span;
+        self.end_value();
+        Ok(event)
+    }
+
+    /// checks the limits that a value starting with `token` would cross
+    fn check_limits(&mut self, token: &Token<'a>) -> Result<(), ParseError> {
+        let limits = self.limits;
+        self.nodes += 1;
+        if let Some(max) = limits.max_nodes.filter(|&max| self.nodes > max) {
+            return Err(self.error(ParseErrorKind::TooManyNodes(max)));
+        }
+        if let Some(Frame::Array { len }) = self.stack.last() {
+            if let Some(max) = limits.max_array_elements.filter(|&max| *len > max) {
+                return Err(self.error(ParseErrorKind::TooManyElements(max)));
+            }
+        }
+        if matches!(token, Token::LBrace | Token::LSquare) {
+            if let Some(max) = limits.max_depth.filter(|&max| self.stack.len() >= max) {
+                return Err(self.error(ParseErrorKind::DepthLimitExceeded(max)));
+            }
+        }
+        Ok(())
+    }
+
+    /// reads an object key and the colon after it
+    fn read_key(&mut self) -> Result<Event<'a>, ParseError> {
+        let key = match self.next_token() {
+            Token::StringLiteral(key) => key,
+            token => return Err(self.unexpected(token)),
+        };
+        let key_span = self.token_span;
+        // the colon is read next, which clears what the lexer kept
+        let wtf8 = self.lexer.wtf8().map(<[u8]>::to_vec);
+        if let Some(Frame::Object { len, .. }) = self.stack.last_mut() {
+            *len += 1;
+            if let Some(max) = self.limits.max_object_keys.filter(|&max| *len > max) {
+                return Err(self.error(ParseErrorKind::TooManyKeys(max)));
+            }
+        }
+        if let Some(Frame::Object {
+            key_spans: Some(key_spans),
+            ..
+        }) = self.stack.last_mut()
+        {
+            if let Some(&first) = key_spans.get(key.as_ref()) {
+                let key = key.into_owned();
+                let kind = ParseErrorKind::DuplicateKey { key, first };
+                return Err(self.error(kind));
+            }
+            key_spans.insert(key.to_string(), key_span);
+        }
+
+        match self.next_token() {
+            Token::Colon => {}
+            Token::StringLiteral(_)
+            | Token::NumberLiteral(_)
+            | Token::BooleanLiteral(_)
+            | Token::NullLiteral
+            | Token::LBrace
+            | Token::RBrace
+            | Token::LSquare
+            | Token::RSquare
+            | Token::Comma => return Err(self.error(ParseErrorKind::MissingColon)),
+            token => return Err(self.unexpected(token)),
+        }
+
+        self.event_span = key_span;
+        self.path.push(Step::Key(key.clone()));
+        self.wtf8 = wtf8;
+        self.state = State::Value;
+        Ok(Event::Key(key))
+    }
+
+    /// moves on to the next element of the innermost array
+    fn start_element(&mut self) {
+        if let Some(Frame::Array { len }) = self.stack.last_mut() {
+            self.path.push(Step::Index(*len));
+            *len += 1;
+        }
+        self.state = State::Value;
+    }
+
+    /// closes the innermost container, whose closing token was just read
+    fn close(&mut self) -> Event<'a> {
+        let event = match self.stack.pop() {
+            Some(Frame::Object { .. }) => Event::EndObject,
+            _ => Event::EndArray,
+        };
+        self.event_span = self.token_span;
+        self.end_value();
+        event
+    }
+
+    /// a value just ended: leave its place in the enclosing container
+    fn end_value(&mut self) {
+        if !self.stack.is_empty() {
+            self.path.pop();
+        }
+        self.state = State::AfterValue;
+    }
+
+    fn next_token(&mut self) -> Token<'a> {
+        let spanned = match self.peeked.take() {
+            Some(spanned) => spanned,
+            None => self.lexer.next_spanned_token(),
+        };
+        self.token_span = spanned.span;
+        spanned.token
+    }
+
+    fn peek_token(&mut self) -> &Token<'a> {
+        let lexer = &mut self.lexer;
+        &self
+            .peeked
+            .get_or_insert_with(|| lexer.next_spanned_token())
+            .token
+    }
+
+    /// builds the error for a token that does not fit the grammar
+    fn unexpected(&self, token: Token<'a>) -> ParseError {
+        let kind = match token {
+            Token::EOF => ParseErrorKind::UnexpectedEof,
+            Token::Illegal(c) => ParseErrorKind::IllegalCharacter(c),
+            Token::InvalidKeyword(key) => ParseErrorKind::InvalidKeyword(key),
+            Token::InvalidNumber(n) => ParseErrorKind::InvalidNumber(n),
+            Token::InvalidEscape(escape) => ParseErrorKind::InvalidEscape(escape),
+            Token::UnterminatedString => ParseErrorKind::UnterminatedString,
+            Token::UnterminatedComment => ParseErrorKind::UnterminatedComment,
+            Token::ControlCharacterInString(c) => ParseErrorKind::ControlCharacterInString(c),
+            Token::InvalidUtf8 => ParseErrorKind::InvalidUtf8,
+            Token::IoError(e) => ParseErrorKind::Io(e),
+            Token::InputTooLarge(max) => ParseErrorKind::InputTooLarge(max),
+            Token::StringTooLong(max) => ParseErrorKind::StringTooLong(max),
+            Token::NumberTooLong(max) => ParseErrorKind::NumberTooLong(max),
+            other => ParseErrorKind::UnexpectedToken(other.to_string()),
+        };
+        self.error(kind)
+    }
+
+    /// reads the token after a container element. Returns whether another
+    /// element follows, or `false` once the closing token was consumed.
+    fn next_separator(&mut self, close: Token<'a>) -> Result<bool, ParseError> {
+        match self.next_token() {
+            Token::Comma => {
+                if *self.peek_token() != close {
+                    return Ok(true);
+                }
+                if !self.allow_trailing_commas {
+                    return Err(self.error(ParseErrorKind::TrailingComma));
+                }
+                self.next_token();
+                Ok(false)
+            }
+            token if token == close => Ok(false),
+            Token::StringLiteral(_)
+            | Token::NumberLiteral(_)
+            | Token::BooleanLiteral(_)
+            | Token::NullLiteral
+            | Token::LBrace
+            | Token::LSquare => Err(self.error(ParseErrorKind::MissingComma)),
+            token => Err(self.unexpected(token)),
+        }
+    }
+}
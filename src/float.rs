@@ -0,0 +1,638 @@
+//! Correctly rounded conversions between JSON numbers and `f64`.
+//!
+//! Parsing tries, in order, exact `f64` arithmetic for short numbers
+//! (Clinger's fast path), the Eisel–Lemire algorithm for everything up to 19
+//! significant digits, and exact big-integer division when neither can
+//! decide the rounding. Formatting generates the shortest digits that read
+//! back to the same `f64` (Steele & White, as refined by Burger & Dybvig).
+
+use std::{cmp::Ordering, sync::OnceLock};
+
+use crate::lexer::is_valid_number;
+
+/// the `f64` nearest to a number in JSON syntax, ties to even. Numbers too
+/// large for an `f64` are infinite and numbers too small are zero. `None`
+/// if `s` is not a JSON number.
+pub fn parse_f64(s: &str) -> Option<f64> {
+    if !is_valid_number(s) {
+        return None;
+    }
+    let (negative, s) = match s.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, s),
+    };
+    let (mantissa, exponent) = match s.find(['e', 'E']) {
+        Some(i) => (&s[..i], parse_exponent(&s[i + 1..])),
+        None => (s, 0),
+    };
+    let (int, frac) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+
+    // the first 19 significant digits, which always fit in a `u64`
+    let mut w = 0u64;
+    let mut taken = 0;
+    let mut dropped = 0i64;
+    let mut inexact = false;
+    for d in int.bytes().chain(frac.bytes()).map(|b| b - b'0') {
+        if taken == 0 && d == 0 {
+            continue;
+        }
+        if taken < 19 {
+            w = w * 10 + d as u64;
+            taken += 1;
+        } else {
+            dropped += 1;
+            inexact |= d != 0;
+        }
+    }
+    let q = exponent
+        .saturating_sub(frac.len() as i64)
+        .saturating_add(dropped);
+
+    let f = if w == 0 {
+        0.0
+    } else if let Some(f) = clinger(w, q).filter(|_| !inexact) {
+        f
+    } else {
+        // with digits dropped, the value lies between `w` and `w + 1`
+        // and both must round the same way
+        match eisel_lemire(w, q) {
+            Some(bits) if !inexact || eisel_lemire(w + 1, q) == Some(bits) => f64::from_bits(bits),
+            _ => exact(int, frac, exponent),
+        }
+    };
+    Some(if negative { -f } else { f })
+}
+
+/// the shortest text that `parse_f64` reads back as `f`, laid out like
+/// Rust's `{:?}`: `0.1`, `100.0`, `1e-7`, `1.5e16`. `NaN`, `Infinity` and
+/// `-Infinity` for the values JSON can't hold.
+pub fn format_f64(f: f64) -> String {
+    if f.is_nan() {
+        return "NaN".to_string();
+    }
+    let sign = if f.is_sign_negative() { "-" } else { "" };
+    let abs = f.abs();
+    if abs.is_infinite() {
+        return format!("{sign}Infinity");
+    }
+    if abs == 0.0 {
+        return format!("{sign}0.0");
+    }
+    if abs < 1e16 && abs.fract() == 0.0 {
+        return format!("{sign}{}.0", abs as u64);
+    }
+
+    let (digits, point) = shortest_digits(abs);
+    let digits: String = digits.iter().map(|&d| char::from(b'0' + d)).collect();
+    if (1e-4..1e16).contains(&abs) {
+        if point <= 0 {
+            let zeros = "0".repeat(point.unsigned_abs() as usize);
+            format!("{sign}0.{zeros}{digits}")
+        } else if point as usize >= digits.len() {
+            let zeros = "0".repeat(point as usize - digits.len());
+            format!("{sign}{digits}{zeros}.0")
+        } else {
+            let (int, frac) = digits.split_at(point as usize);
+            format!("{sign}{int}.{frac}")
+        }
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let dot = if rest.is_empty() { "" } else { "." };
+        format!("{sign}{first}{dot}{rest}e{}", point - 1)
+    }
+}
+
+/// the exponent, saturated far beyond anything an `f64` can reach
+fn parse_exponent(s: &str) -> i64 {
+    let (negative, digits) = match s.as_bytes().first() {
+        Some(b'-') => (true, &s[1..]),
+        Some(b'+') => (false, &s[1..]),
+        _ => (false, s),
+    };
+    let e = digits
+        .bytes()
+        .fold(0i64, |e, b| (e * 10 + (b - b'0') as i64).min(1 << 40));
+    if negative {
+        -e
+    } else {
+        e
+    }
+}
+
+const POWERS_OF_TEN: [f64; 23] = [
+    1e0, 1e1, 1e2, 1e3, 1e4, 1e5, 1e6, 1e7, 1e8, 1e9, 1e10, 1e11, 1e12, 1e13, 1e14, 1e15, 1e16,
+    1e17, 1e18, 1e19, 1e20, 1e21, 1e22,
+];
+
+/// `w × 10^q` when both factors are exact in an `f64`, so the single
+/// rounding of the multiplication or division is the correct one
+fn clinger(w: u64, q: i64) -> Option<f64> {
+    const MAX_EXACT: u64 = 1 << 53;
+    if w > MAX_EXACT {
+        return None;
+    }
+    match q {
+        -22..=-1 => Some(w as f64 / POWERS_OF_TEN[-q as usize]),
+        0..=22 => Some(w as f64 * POWERS_OF_TEN[q as usize]),
+        // a small mantissa can absorb some of the exponent exactly
+        23..=37 => w
+            .checked_mul(10u64.pow(q as u32 - 22))
+            .filter(|&w| w <= MAX_EXACT)
+            .map(|w| w as f64 * 1e22),
+        _ => None,
+    }
+}
+
+const SMALLEST_POWER: i64 = -342;
+const LARGEST_POWER: i64 = 308;
+
+/// the bits of the `f64` nearest to `w × 10^q`, or `None` when the 128-bit
+/// approximation of `5^q` is too coarse to decide the rounding
+fn eisel_lemire(w: u64, q: i64) -> Option<u64> {
+    if q < SMALLEST_POWER {
+        return Some(0);
+    }
+    if q > LARGEST_POWER {
+        return Some(f64::INFINITY.to_bits());
+    }
+
+    let lz = w.leading_zeros();
+    let w = w << lz;
+    let (lo, hi) = product_approx(q, w);
+    // the truncated product may be one below a carry that flips the
+    // rounding, which only matters where the power isn't exact
+    if lo == u64::MAX && !(-27..=55).contains(&q) {
+        return None;
+    }
+
+    let upperbit = (hi >> 63) as i32;
+    let shift = upperbit + 64 - 52 - 3;
+    let mut mantissa = hi >> shift;
+    // floor(q × log2(10)) + 63, the binary exponent of the product
+    let power = ((q as i32).wrapping_mul(152_170 + 65536) >> 16) + 63;
+    let mut power2 = power + upperbit - lz as i32 + 1023;
+    if power2 <= 0 {
+        if -power2 + 1 >= 64 {
+            return Some(0);
+        }
+        // subnormal
+        mantissa >>= -power2 + 1;
+        mantissa += mantissa & 1;
+        mantissa >>= 1;
+        let power2 = (mantissa >= 1 << 52) as u64;
+        return Some(power2 << 52 | mantissa & ((1 << 52) - 1));
+    }
+
+    // exactly halfway between two floats: round to even, down
+    if lo <= 1 && (-4..=23).contains(&q) && mantissa & 3 == 1 && mantissa << shift == hi {
+        mantissa &= !1;
+    }
+    mantissa += mantissa & 1;
+    mantissa >>= 1;
+    if mantissa >= 2 << 52 {
+        mantissa = 1 << 52;
+        power2 += 1;
+    }
+    mantissa &= !(1 << 52);
+    if power2 >= 0x7FF {
+        return Some(f64::INFINITY.to_bits());
+    }
+    Some((power2 as u64) << 52 | mantissa)
+}
+
+/// the high 128 bits of `w × 5^q`, as (low, high) halves
+fn product_approx(q: i64, w: u64) -> (u64, u64) {
+    let power = powers_of_five()[(q - SMALLEST_POWER) as usize];
+    let (lo5, hi5) = (power as u64, (power >> 64) as u64);
+    let first = w as u128 * hi5 as u128;
+    let (mut lo, mut hi) = (first as u64, (first >> 64) as u64);
+
+    // enough bits for the mantissa, the rounding bit and a leading zero,
+    // unless they are all ones and a carry may still come from below
+    let mask = u64::MAX >> 55;
+    if hi & mask == mask {
+        let second = ((w as u128 * lo5 as u128) >> 64) as u64;
+        lo = lo.wrapping_add(second);
+        if second > lo {
+            hi += 1;
+        }
+    }
+    (lo, hi)
+}
+
+/// `5^q` for every `q` Eisel–Lemire handles, normalized so the top bit of
+/// the 128 is set. Positive powers are truncated; negative powers are
+/// rounded up, as the algorithm expects.
+fn powers_of_five() -> &'static [u128] {
+    static POWERS: OnceLock<Vec<u128>> = OnceLock::new();
+    POWERS.get_or_init(|| {
+        (SMALLEST_POWER..=LARGEST_POWER)
+            .map(|q| {
+                let mut power = Big::from_u64(1);
+                power.mul_pow5(q.unsigned_abs() as usize);
+                let z = power.bit_len();
+                if q >= 0 {
+                    if z > 128 {
+                        power.shr(z - 128);
+                    } else {
+                        power.shl(128 - z);
+                    }
+                    power.to_u128()
+                } else if q >= -27 {
+                    quotient(&Big::pow2(z + 127), &power).0 + 1
+                } else {
+                    // the top 128 bits of floor(2^b / 5^-q) + 1
+                    let mut numerator = Big::pow2(2 * z + 128);
+                    numerator.add(&power);
+                    let mut denominator = power;
+                    denominator.shl(z + 1);
+                    quotient(&numerator, &denominator).0
+                }
+            })
+            .collect()
+    })
+}
+
+/// the slow path: divides the decimal digits by the power of ten exactly
+fn exact(int: &str, frac: &str, exponent: i64) -> f64 {
+    // halfway points between floats need at most 767 significant digits,
+    // so the rest only matter for whether they are all zero
+    const MAX_DIGITS: usize = 780;
+
+    let mut digits: Vec<u8> = int
+        .bytes()
+        .chain(frac.bytes())
+        .map(|b| b - b'0')
+        .skip_while(|&d| d == 0)
+        .collect();
+    let mut e = exponent.saturating_sub(frac.len() as i64);
+    if digits.len() > MAX_DIGITS {
+        let nonzero = digits[MAX_DIGITS..].iter().any(|&d| d != 0);
+        e = e.saturating_add((digits.len() - MAX_DIGITS) as i64);
+        digits.truncate(MAX_DIGITS);
+        if nonzero {
+            digits.push(1);
+            e -= 1;
+        }
+    }
+
+    // 0.d × 10^point: above the largest or below half the smallest f64
+    let point = digits.len() as i64 + e;
+    if point > 310 {
+        return f64::INFINITY;
+    }
+    if point < -325 {
+        return 0.0;
+    }
+
+    let mut numerator = Big::from_digits(&digits);
+    let mut denominator = Big::from_u64(1);
+    if e >= 0 {
+        numerator.mul_pow10(e as usize);
+    } else {
+        denominator.mul_pow10(-e as usize);
+    }
+
+    // a quotient of 65 or 66 bits, scaled back by `-scale`
+    let scale = 65 - (numerator.bit_len() as i64 - denominator.bit_len() as i64);
+    if scale >= 0 {
+        numerator.shl(scale as usize);
+    } else {
+        denominator.shl(-scale as usize);
+    }
+    let (q, inexact) = quotient(&numerator, &denominator);
+    let extra = 64 - q.leading_zeros();
+    let sticky = inexact || q & ((1 << extra) - 1) != 0;
+    round((q >> extra) as u64, extra as i64 - scale, sticky)
+}
+
+/// rounds `m × 2^e`, with `m` normalized and `sticky` set if anything
+/// nonzero lies below it, to the nearest `f64`, ties to even
+fn round(m: u64, e: i64, sticky: bool) -> f64 {
+    // the binary exponent of the leading bit
+    let top = e + 63;
+    if top > 1023 {
+        return f64::INFINITY;
+    }
+    // bits below the 53 that fit, more for subnormals
+    let shift = if top >= -1022 { 11 } else { 11 + (-1022 - top) };
+    if shift > 64 {
+        return 0.0;
+    }
+
+    let (mut mantissa, round_up) = if shift == 64 {
+        (0, m << 1 != 0 || sticky)
+    } else {
+        let rest = m & ((1 << shift) - 1);
+        let half = 1 << (shift - 1);
+        let mantissa = m >> shift;
+        let up = rest > half || (rest == half && (sticky || mantissa & 1 == 1));
+        (mantissa, up)
+    };
+    if round_up {
+        mantissa += 1;
+    }
+    // a carry out of the mantissa moves into the exponent bits by itself
+    let bits = if top >= -1022 {
+        (((top + 1022) as u64) << 52) + mantissa
+    } else {
+        mantissa
+    };
+    f64::from_bits(bits)
+}
+
+/// the shortest digits that read back as `f`, and the position of the
+/// decimal point: `f ≈ 0.d₁d₂… × 10^point`
+fn shortest_digits(f: f64) -> (Vec<u8>, i32) {
+    let bits = f.to_bits();
+    let biased = (bits >> 52) as i64 & 0x7FF;
+    let fraction = bits & ((1 << 52) - 1);
+    let (m, e) = if biased == 0 {
+        (fraction, -1074)
+    } else {
+        (fraction | 1 << 52, biased - 1075)
+    };
+    // the parser rounds ties to even, so an even mantissa also owns the
+    // halfway points around it
+    let inclusive = m & 1 == 0;
+    // at a power of two the float below is closer than the one above
+    let closer_below = fraction == 0 && biased > 1;
+
+    // f = r / s, with the halfway points to the neighbouring floats at
+    // (r - minus) / s and (r + plus) / s
+    let mut r = Big::from_u64(m);
+    let (mut s, mut plus, mut minus);
+    if e >= 0 {
+        let e = e as usize;
+        if closer_below {
+            r.shl(e + 2);
+            s = Big::from_u64(4);
+            plus = Big::pow2(e + 1);
+        } else {
+            r.shl(e + 1);
+            s = Big::from_u64(2);
+            plus = Big::pow2(e);
+        }
+        minus = Big::pow2(e);
+    } else {
+        let e = -e as usize;
+        if closer_below {
+            r.shl(2);
+            s = Big::pow2(e + 2);
+            plus = Big::from_u64(2);
+        } else {
+            r.shl(1);
+            s = Big::pow2(e + 1);
+            plus = Big::from_u64(1);
+        }
+        minus = Big::from_u64(1);
+    }
+
+    // never too high, at most one too low
+    let mut point = (f.log10() - 1e-10).ceil() as i32;
+    if point >= 0 {
+        s.mul_pow10(point as usize);
+    } else {
+        let k = -point as usize;
+        r.mul_pow10(k);
+        plus.mul_pow10(k);
+        minus.mul_pow10(k);
+    }
+    let above = |r: &Big, plus: &Big, s: &Big| {
+        let mut high = r.clone();
+        high.add(plus);
+        match high.cmp(s) {
+            Ordering::Greater => true,
+            Ordering::Equal => inclusive,
+            Ordering::Less => false,
+        }
+    };
+    while above(&r, &plus, &s) {
+        s.mul_small(10);
+        point += 1;
+    }
+
+    let mut digits = vec![];
+    loop {
+        r.mul_small(10);
+        plus.mul_small(10);
+        minus.mul_small(10);
+        let mut d = 0;
+        while r.cmp(&s) != Ordering::Less {
+            r.sub(&s);
+            d += 1;
+        }
+
+        let low = match r.cmp(&minus) {
+            Ordering::Less => true,
+            Ordering::Equal => inclusive,
+            Ordering::Greater => false,
+        };
+        let high = above(&r, &plus, &s);
+        if !low && !high {
+            digits.push(d);
+            continue;
+        }
+        let up = if low && high {
+            // closest of the two, ties up
+            let mut twice = r.clone();
+            twice.shl(1);
+            twice.cmp(&s) != Ordering::Less
+        } else {
+            high
+        };
+        digits.push(d);
+        if up {
+            round_up(&mut digits, &mut point);
+        }
+        return (digits, point);
+    }
+}
+
+fn round_up(digits: &mut Vec<u8>, point: &mut i32) {
+    while let Some(last) = digits.last_mut() {
+        if *last < 9 {
+            *last += 1;
+            return;
+        }
+        digits.pop();
+    }
+    digits.push(1);
+    *point += 1;
+}
+
+/// `floor(n / d)` and whether it left a remainder, for quotients that fit
+/// in 128 bits
+fn quotient(n: &Big, d: &Big) -> (u128, bool) {
+    let (nb, db) = (n.bit_len(), d.bit_len());
+    if nb < db {
+        return (0, !n.is_zero());
+    }
+    let shift = nb - db;
+    let mut d = d.clone();
+    d.shl(shift);
+    let mut r = n.clone();
+    let mut q = 0u128;
+    for _ in 0..=shift {
+        q <<= 1;
+        if r.cmp(&d) != Ordering::Less {
+            r.sub(&d);
+            q |= 1;
+        }
+        d.shr(1);
+    }
+    (q, !r.is_zero())
+}
+
+/// An unsigned integer of any size, least significant 32-bit limb first,
+/// without high zero limbs.
+#[derive(Debug, Clone)]
+struct Big {
+    limbs: Vec<u32>,
+}
+
+impl Big {
+    fn from_u64(n: u64) -> Self {
+        let mut big = Self {
+            limbs: vec![n as u32, (n >> 32) as u32],
+        };
+        big.trim();
+        big
+    }
+
+    fn pow2(n: usize) -> Self {
+        let mut big = Self::from_u64(1);
+        big.shl(n);
+        big
+    }
+
+    fn from_digits(digits: &[u8]) -> Self {
+        let mut big = Self { limbs: vec![] };
+        for chunk in digits.chunks(9) {
+            let n = chunk.iter().fold(0u32, |n, &d| n * 10 + d as u32);
+            big.mul_small(10u32.pow(chunk.len() as u32));
+            big.add(&Self::from_u64(n as u64));
+        }
+        big
+    }
+
+    fn to_u128(&self) -> u128 {
+        self.limbs
+            .iter()
+            .rev()
+            .fold(0u128, |n, &limb| n << 32 | limb as u128)
+    }
+
+    fn trim(&mut self) {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    fn bit_len(&self) -> usize {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() * 32 - top.leading_zeros() as usize,
+            None => 0,
+        }
+    }
+
+    fn mul_small(&mut self, m: u32) {
+        let mut carry = 0u64;
+        for limb in &mut self.limbs {
+            let product = *limb as u64 * m as u64 + carry;
+            *limb = product as u32;
+            carry = product >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+        self.trim();
+    }
+
+    fn mul_pow5(&mut self, mut n: usize) {
+        // 5^13 is the largest power of five that fits in a limb
+        while n >= 13 {
+            self.mul_small(1_220_703_125);
+            n -= 13;
+        }
+        self.mul_small(5u32.pow(n as u32));
+    }
+
+    fn mul_pow10(&mut self, n: usize) {
+        self.mul_pow5(n);
+        self.shl(n);
+    }
+
+    fn shl(&mut self, n: usize) {
+        if self.is_zero() {
+            return;
+        }
+        let bits = n % 32;
+        if bits > 0 {
+            let mut carry = 0;
+            for limb in &mut self.limbs {
+                let shifted = (*limb as u64) << bits | carry;
+                *limb = shifted as u32;
+                carry = shifted >> 32;
+            }
+            if carry > 0 {
+                self.limbs.push(carry as u32);
+            }
+        }
+        self.limbs.splice(0..0, std::iter::repeat_n(0, n / 32));
+    }
+
+    fn shr(&mut self, n: usize) {
+        self.limbs.drain(..(n / 32).min(self.limbs.len()));
+        let bits = n % 32;
+        if bits > 0 {
+            let mut carry = 0;
+            for limb in self.limbs.iter_mut().rev() {
+                let shifted = *limb >> bits | carry;
+                carry = *limb << (32 - bits);
+                *limb = shifted;
+            }
+        }
+        self.trim();
+    }
+
+    fn add(&mut self, other: &Self) {
+        if self.limbs.len() < other.limbs.len() {
+            self.limbs.resize(other.limbs.len(), 0);
+        }
+        let mut carry = 0u64;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let sum = *limb as u64 + *other.limbs.get(i).unwrap_or(&0) as u64 + carry;
+            *limb = sum as u32;
+            carry = sum >> 32;
+        }
+        if carry > 0 {
+            self.limbs.push(carry as u32);
+        }
+    }
+
+    /// `self - other`, for `self >= other`
+    fn sub(&mut self, other: &Self) {
+        let mut borrow = 0i64;
+        for (i, limb) in self.limbs.iter_mut().enumerate() {
+            let mut diff = *limb as i64 - *other.limbs.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = (diff < 0) as i64;
+            if diff < 0 {
+                diff += 1 << 32;
+            }
+            *limb = diff as u32;
+        }
+        self.trim();
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs
+            .len()
+            .cmp(&other.limbs.len())
+            .then_with(|| self.limbs.iter().rev().cmp(other.limbs.iter().rev()))
+    }
+}
@@ -1,220 +1,737 @@
-use super::token::Token;
+use std::{borrow::Cow, io::Read};
 
-pub struct Lexer {
-    /// String to analyze
-    input: Vec<char>,
+use super::{
+    limits::Limits,
+    source::Source,
+    token::{Span, SpannedToken, Token},
+};
 
-    /// current position in input (points to current char)
-    position: usize,
+/// What to do with a `\uXXXX` escape that encodes half of a UTF-16
+/// surrogate pair without its other half.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoneSurrogatePolicy {
+    /// reject the string with `Token::InvalidEscape`
+    #[default]
+    Error,
+    /// decode it as U+FFFD REPLACEMENT CHARACTER
+    Replace,
+    /// keep it as WTF-8. A Rust `String` can't hold a lone surrogate, so
+    /// the token still has U+FFFD in its place, and the exact bytes are
+    /// given by `Lexer::wtf8` and `EventReader::wtf8`.
+    Preserve,
+}
+
+/// Longest text of an invalid number or keyword kept for its error, so a
+/// huge one is neither buffered nor copied whole.
+const MAX_INVALID_TEXT: usize = 64;
+
+/// A `\uXXXX` escape read from a string.
+struct Escape {
+    /// the UTF-16 code unit it encodes
+    unit: u32,
+
+    /// the escape as written in the source
+    text: String,
+
+    span: Span,
+}
+
+/// Splits UTF-8 input into tokens. It works on bytes and only decodes the
+/// characters it has to, checking that the input is valid UTF-8 as it goes.
+pub struct Lexer<'a> {
+    /// where the bytes come from
+    source: Source<'a>,
+
+    /// current byte under examination
+    ch: Option<u8>,
+
+    /// byte after the current one
+    next: Option<u8>,
+
+    /// error token to report once the source stops early
+    failure: Option<Token<'a>>,
+
+    /// offset of the current byte
+    offset: usize,
 
-    /// current reading position in input (after current char)
-    next_position: usize,
+    /// line of the current byte, starting at 1
+    line: usize,
 
-    /// current char under examination
-    ch: Option<char>,
+    /// column of the char the current byte belongs to, starting at 1
+    column: usize,
+
+    /// where the token being read starts
+    token_span: Span,
+
+    lone_surrogates: LoneSurrogatePolicy,
+
+    /// lone surrogates kept in the string being read, as the offset of
+    /// their U+FFFD in it and their code unit
+    preserved: Vec<(usize, u32)>,
+
+    /// the last string token as WTF-8, if it kept lone surrogates
+    wtf8: Option<Vec<u8>>,
+
+    /// only the input, string and number limits apply here
+    limits: Limits,
+
+    /// bytes taken from the source so far
+    pulled: usize,
+
+    /// skip `// line` and `/* block */` comments like whitespace
+    comments: bool,
+
+    /// read `NaN`, `Infinity` and `-Infinity` as numbers
+    nan_infinity: bool,
+
+    /// accept strings in single quotes, and `\'` escapes
+    single_quotes: bool,
 }
 
-impl Iterator for Lexer {
-    type Item = Token;
+impl<'a> Iterator for Lexer<'a> {
+    type Item = SpannedToken<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let token = self.next_token();
-        match token {
+        let spanned = self.next_spanned_token();
+        match spanned.token {
             Token::EOF => None,
-            _ => Some(token),
+            _ => Some(spanned),
         }
     }
 }
 
-impl Lexer {
-    pub fn new(input: Vec<char>) -> Self {
+impl<'a> Lexer<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::from_source(Source::from_str(input))
+    }
+
+    /// lexes UTF-8 bytes in place. Invalid UTF-8 is reported as
+    /// `Token::InvalidUtf8` where it is found.
+    pub fn from_slice(input: &'a [u8]) -> Self {
+        Self::from_source(Source::from_slice(input))
+    }
+
+    /// reads UTF-8 text from `reader` through a fixed-size buffer, so the
+    /// input never has to be in memory at once
+    pub fn from_reader(reader: impl Read + 'a) -> Self {
+        Self::from_source(Source::from_reader(reader))
+    }
+
+    fn from_source(source: Source<'a>) -> Self {
         let mut l = Self {
-            input,
-            position: 0,
-            next_position: 0,
+            source,
             ch: None,
+            next: None,
+            failure: None,
+            offset: 0,
+            line: 1,
+            column: 1,
+            token_span: Span::default(),
+            lone_surrogates: LoneSurrogatePolicy::default(),
+            preserved: vec![],
+            wtf8: None,
+            limits: Limits::default(),
+            pulled: 0,
+            comments: false,
+            nan_infinity: false,
+            single_quotes: false,
         };
+        l.next = l.pull();
         l.read_char();
         l
     }
 
+    pub fn with_lone_surrogate_policy(mut self, policy: LoneSurrogatePolicy) -> Self {
+        self.lone_surrogates = policy;
+        self
+    }
+
+    /// reports `Token::InputTooLarge`, `Token::StringTooLong` and
+    /// `Token::NumberTooLong` once the matching limit is crossed. The other
+    /// limits are checked by `EventReader`.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.comments = allow;
+        self
+    }
+
+    pub fn allow_nan_infinity(mut self, allow: bool) -> Self {
+        self.nan_infinity = allow;
+        self
+    }
+
+    pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+        self.single_quotes = allow;
+        self
+    }
+
     /// get and analyze next token, returning the correct TokenType
-    pub fn next_token(&mut self) -> Token {
-        self.skip_whitespace();
-
-        let t = match self.ch {
-            None => Token::EOF,
-            Some(c) => match c {
-                '[' => Token::LSquare,
-                ']' => Token::RSquare,
-                '{' => Token::LBrace,
-                '}' => Token::RBrace,
-                ',' => Token::Comma,
-                ':' => Token::Colon,
-                'n' => self.read_keyword("null", Token::NullLiteral),
-                't' => self.read_keyword("true", Token::BooleanLiteral(true)),
-                'f' => self.read_keyword("false", Token::BooleanLiteral(false)),
-                'a'..='z' | 'A'..='Z' => self.read_identifier_from(self.position),
-                '0'..='9' | '-' => self.read_number(),
-                '"' => {
-                    let text = self.read_string();
-                    Token::StringLiteral(text)
-                }
-                _ => Token::Illegal(c),
-            },
+    pub fn next_token(&mut self) -> Token<'a> {
+        self.next_spanned_token().token
+    }
+
+    /// the string token just read as WTF-8, when
+    /// `LoneSurrogatePolicy::Preserve` kept a lone surrogate in it. The token
+    /// has U+FFFD where these bytes have the surrogate.
+    pub fn wtf8(&self) -> Option<&[u8]> {
+        self.wtf8.as_deref()
+    }
+
+    /// same as `next_token`, but also reports where the token was found
+    pub fn next_spanned_token(&mut self) -> SpannedToken<'a> {
+        self.wtf8 = None;
+        let t = match self.skip_whitespace() {
+            Err(t) => t,
+            Ok(()) => {
+                self.token_span = self.here();
+                match self.ch {
+                    None => self.failure.clone().unwrap_or(Token::EOF),
+                    Some(c) => match c {
+                        b'[' => Token::LSquare,
+                        b']' => Token::RSquare,
+                        b'{' => Token::LBrace,
+                        b'}' => Token::RBrace,
+                        b',' => Token::Comma,
+                        b':' => Token::Colon,
+                        b'a'..=b'z' | b'A'..=b'Z' => self.read_keyword(),
+                        b'-' if self.nan_infinity && self.peek() == Some(b'I') => {
+                            self.read_negative_infinity()
+                        }
+                        b'0'..=b'9' | b'-' => self.read_number(),
+                        b'"' => self.read_string(),
+                        b'\'' if self.single_quotes => self.read_string(),
+                        0x00..=0x7F => Token::Illegal(c as char),
+                        _ => match self.read_utf8_char() {
+                            Ok(c) => Token::Illegal(c),
+                            Err(t) => t,
+                        },
+                    },
+                }
+            }
+        };
+        let span = Span {
+            end: self.offset + usize::from(self.ch.is_some()),
+            ..self.token_span
         };
         self.read_char();
-        t
+        SpannedToken { token: t, span }
+    }
+
+    /// position of the current byte, as an empty span
+    fn here(&self) -> Span {
+        Span {
+            start: self.offset,
+            end: self.offset,
+            line: self.line,
+            column: self.column,
+        }
     }
 
     fn read_char(&mut self) {
-        self.ch = self.peek();
+        if let Some(c) = self.ch {
+            self.offset += 1;
+            if c == b'\n' {
+                self.line += 1;
+                self.column = 1;
+            } else if !self.next.is_some_and(is_continuation) {
+                self.column += 1;
+            }
+        }
+        self.ch = self.next;
         if self.ch.is_some() {
-            self.position = self.next_position;
-            self.next_position += 1;
+            self.next = self.pull();
         }
     }
 
-    pub fn peek(&self) -> Option<char> {
-        self.input.get(self.next_position).copied()
+    /// takes the next byte from the source, remembering why it stopped
+    /// if it fails
+    fn pull(&mut self) -> Option<u8> {
+        match self.source.next_byte() {
+            Ok(Some(b)) => {
+                self.pulled += 1;
+                if let Some(max) = self.limits.max_input_bytes.filter(|&max| self.pulled > max) {
+                    self.failure = Some(Token::InputTooLarge(max));
+                    return None;
+                }
+                Some(b)
+            }
+            Ok(None) => None,
+            Err(e) => {
+                self.failure = Some(Token::IoError(e.to_string()));
+                None
+            }
+        }
     }
 
-    fn read_number(&mut self) -> Token {
-        let start_position = self.position;
-        while let Some(c) = self.peek() {
-            if c.is_ascii_digit() {
-                self.read_char();
-            } else {
-                break;
-            }
+    fn peek(&self) -> Option<u8> {
+        self.next
+    }
+
+    /// the failure that stopped the source after the current byte, since
+    /// the token under it may have gone on
+    fn cut_short(&mut self) -> Option<Token<'a>> {
+        if self.peek().is_some() {
+            return None;
         }
+        let failure = self.failure.clone()?;
+        self.token_span = self.here();
+        Some(failure)
+    }
 
-        if self.peek() == Some('.') {
-            self.read_char();
-            // read the fractional part, if any.
-            while let Some(c) = self.peek() {
-                if c.is_ascii_digit() {
+    /// decodes the multi-byte UTF-8 sequence that starts at the current
+    /// byte. Leaves the current byte on its last byte.
+    fn read_utf8_char(&mut self) -> Result<char, Token<'a>> {
+        let span = self.here();
+        let first = self.ch.unwrap_or_default();
+        let width = match first {
+            0xC2..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF4 => 4,
+            _ => 0,
+        };
+
+        let mut bytes = [first, 0, 0, 0];
+        let mut valid = width > 0;
+        for b in bytes.iter_mut().take(width).skip(1) {
+            match self.peek() {
+                Some(next) if is_continuation(next) => {
                     self.read_char();
-                } else {
+                    *b = next;
+                }
+                _ => {
+                    if let Some(failure) = self.cut_short() {
+                        return Err(failure);
+                    }
+                    valid = false;
                     break;
                 }
             }
         }
 
-        // read the exponent, if any.
-        if self.peek() == Some('e') || self.peek() == Some('E') {
-            self.read_char();
+        // also rejects overlong forms and encoded surrogates
+        let decoded = valid
+            .then(|| std::str::from_utf8(&bytes[..width]).ok())
+            .flatten()
+            .and_then(|s| s.chars().next());
+        decoded.ok_or_else(|| {
+            self.token_span = span;
+            Token::InvalidUtf8
+        })
+    }
 
-            if self.peek() == Some('+') || self.peek() == Some('-') {
-                self.read_char();
+    fn read_number(&mut self) -> Token<'a> {
+        let start = self.offset;
+        // only filled when the number can't be borrowed from the input
+        let mut owned = String::new();
+        let borrow = self.source.can_borrow();
+        if !borrow {
+            owned.push(self.ch.unwrap_or_default() as char);
+        }
+        let mut digits = usize::from(self.ch.is_some_and(|c| c.is_ascii_digit()));
+        let (mut point, mut exponent, mut invalid) = (false, false, false);
+        // take everything that could belong to a number, so a malformed one
+        // is reported as one token instead of being split into several
+        while let Some(c) = self.peek() {
+            match c {
+                b'0'..=b'9' => digits += 1,
+                b'.' => {
+                    invalid |= point || exponent;
+                    point = true;
+                }
+                b'e' | b'E' => {
+                    invalid |= exponent;
+                    exponent = true;
+                }
+                b'+' | b'-' => invalid |= !matches!(self.ch, Some(b'e' | b'E')),
+                _ => break,
             }
-
-            while let Some(c) = self.peek() {
-                if c.is_ascii_digit() {
-                    self.read_char();
-                } else {
-                    break;
+            if let Some(max) = self.limits.max_number_digits {
+                // a valid number has at most a sign, a point, an `e` and
+                // the sign of its exponent besides its digits
+                if digits > max || self.offset + 2 - start > max.saturating_add(4) {
+                    return Token::NumberTooLong(max);
                 }
             }
+            self.read_char();
+            if !borrow && (!invalid || owned.len() < MAX_INVALID_TEXT) {
+                owned.push(c as char);
+            }
+        }
+        if let Some(failure) = self.cut_short() {
+            return failure;
         }
 
-        let end_position = self.position;
-        let identifier = self.input[start_position..=end_position].iter().collect();
-        Token::NumberLiteral(identifier)
+        let number = match self.source.borrow(start, self.offset + 1) {
+            Some(number) => Cow::Borrowed(number),
+            None => Cow::Owned(owned),
+        };
+        if !invalid && is_valid_number(&number) {
+            Token::NumberLiteral(number)
+        } else {
+            Token::InvalidNumber(invalid_text(&number, self.offset + 1 - start))
+        }
     }
 
-    fn read_string(&mut self) -> String {
+    /// reads a string delimited by the quote under the current byte
+    fn read_string(&mut self) -> Token<'a> {
+        let quote = self.ch.unwrap_or(b'"');
         self.read_char();
-        let mut result = String::new();
+        let start = self.offset;
+        self.preserved.clear();
+
+        // fast path: a string without escapes is borrowed from the input
+        if self.source.can_borrow() {
+            while let Some(c) = self.ch {
+                if let Some(t) = self.check_string_length(start) {
+                    return t;
+                }
+                match c {
+                    c if c == quote => {
+                        if let Some(s) = self.source.borrow(start, self.offset) {
+                            return Token::StringLiteral(Cow::Borrowed(s));
+                        }
+                        break;
+                    }
+                    b'\\' | 0x00..=0x1F => break,
+                    0x20..=0x7F => {}
+                    _ => {
+                        if let Err(t) = self.read_utf8_char() {
+                            return t;
+                        }
+                    }
+                }
+                self.read_char();
+            }
+        }
+
+        // what was scanned so far is plain text, the rest is decoded below
+        let mut result = String::from(self.source.borrow(start, self.offset).unwrap_or_default());
+        // high surrogate waiting for its low half
+        let mut pending_high: Option<Escape> = None;
 
         while let Some(c) = self.ch {
+            if let Some(t) = self.check_string_length(start) {
+                return t;
+            }
+            if c == b'\\' && self.peek() == Some(b'u') {
+                let escape = match self.read_unicode_escape() {
+                    Ok(escape) => escape,
+                    Err(t) => return t,
+                };
+
+                if let Some(high) = pending_high.take() {
+                    if (0xDC00..=0xDFFF).contains(&escape.unit) {
+                        let scalar =
+                            0x10000 + ((high.unit - 0xD800) << 10) + (escape.unit - 0xDC00);
+                        result.extend(char::from_u32(scalar));
+                        self.read_char();
+                        continue;
+                    }
+                    if let Err(t) = self.push_lone_surrogate(&mut result, high) {
+                        return t;
+                    }
+                }
+
+                match escape.unit {
+                    0xD800..=0xDBFF => pending_high = Some(escape),
+                    0xDC00..=0xDFFF => {
+                        if let Err(t) = self.push_lone_surrogate(&mut result, escape) {
+                            return t;
+                        }
+                    }
+                    unit => result.extend(char::from_u32(unit)),
+                }
+                self.read_char();
+                continue;
+            }
+
+            if let Some(high) = pending_high.take() {
+                if let Err(t) = self.push_lone_surrogate(&mut result, high) {
+                    return t;
+                }
+            }
+
             match c {
-                '"' => break,
-                '\\' => {
+                c if c == quote => {
+                    self.wtf8 = self.preserved_wtf8(&result);
+                    return Token::StringLiteral(Cow::Owned(result));
+                }
+                0x00..=0x1F => {
+                    self.token_span = self.here();
+                    return Token::ControlCharacterInString(c as char);
+                }
+                b'\\' => {
+                    let escape_span = self.here();
                     self.read_char();
                     match self.ch {
-                        Some('"') => result.push('"'),
-                        Some('\\') => result.push('\\'),
-                        Some('n') => result.push('\n'), // \n -> newline
-                        Some('t') => result.push('\t'), // \t -> tab
-                        Some('r') => result.push('\r'), // \r -> carriage return
-                        Some('f') => result.push('\u{000C}'), // \f -> form feed
-                        Some('b') => result.push('\u{0008}'), // \b -> backspace
-                        Some('/') => result.push('/'),  // \/ -> /
+                        Some(b'"') => result.push('"'),
+                        Some(b'\\') => result.push('\\'),
+                        Some(b'n') => result.push('\n'), // \n -> newline
+                        Some(b't') => result.push('\t'), // \t -> tab
+                        Some(b'r') => result.push('\r'), // \r -> carriage return
+                        Some(b'f') => result.push('\x0C'), // \f -> form feed
+                        Some(b'b') => result.push('\x08'), // \b -> backspace
+                        Some(b'/') => result.push('/'),  // \/ -> /
+                        Some(b'\'') if self.single_quotes => result.push('\''),
                         Some(other) => {
-                            result.push('\\');
-                            result.push(other);
+                            let other = match other {
+                                0x00..=0x7F => other as char,
+                                _ => match self.read_utf8_char() {
+                                    Ok(c) => c,
+                                    Err(t) => return t,
+                                },
+                            };
+                            self.token_span = escape_span;
+                            return Token::InvalidEscape(format!("\\{other}"));
                         }
                         None => break,
                     }
                 }
-                other => result.push(other),
+                0x20..=0x7F => result.push(c as char),
+                _ => match self.read_utf8_char() {
+                    Ok(c) => result.push(c),
+                    Err(t) => return t,
+                },
             }
             self.read_char();
         }
-        result
+        if let Some(failure) = self.failure.clone() {
+            self.token_span = self.here();
+            return failure;
+        }
+        Token::UnterminatedString
     }
 
-    fn read_char_is_matched(&mut self, expected: char) -> bool {
-        if let Some(c) = self.peek() {
-            if c == expected {
-                self.read_char();
-                return true;
+    /// `Token::StringTooLong` once the string that started at `start` has
+    /// more bytes than allowed
+    fn check_string_length(&self, start: usize) -> Option<Token<'a>> {
+        let max = self
+            .limits
+            .max_string_bytes
+            .filter(|&max| self.offset - start > max)?;
+        Some(Token::StringTooLong(max))
+    }
+
+    /// reads a `\uXXXX` escape, with the current byte on the backslash.
+    /// Leaves the current byte on the last hex digit.
+    fn read_unicode_escape(&mut self) -> Result<Escape, Token<'a>> {
+        let span = self.here();
+        let mut text = String::from("\\u");
+        self.read_char();
+
+        let mut unit = 0;
+        for _ in 0..4 {
+            match self.peek() {
+                Some(c) if c.is_ascii_hexdigit() => {
+                    self.read_char();
+                    text.push(c as char);
+                    unit = unit * 16 + (c as char).to_digit(16).unwrap_or_default();
+                }
+                _ => {
+                    if let Some(failure) = self.cut_short() {
+                        return Err(failure);
+                    }
+                    self.token_span = span;
+                    return Err(Token::InvalidEscape(text));
+                }
+            }
+        }
+        Ok(Escape { unit, text, span })
+    }
+
+    fn push_lone_surrogate(
+        &mut self,
+        result: &mut String,
+        escape: Escape,
+    ) -> Result<(), Token<'a>> {
+        match self.lone_surrogates {
+            LoneSurrogatePolicy::Error => {
+                self.token_span = escape.span;
+                Err(Token::InvalidEscape(escape.text))
+            }
+            LoneSurrogatePolicy::Replace => {
+                result.push(char::REPLACEMENT_CHARACTER);
+                Ok(())
             }
+            LoneSurrogatePolicy::Preserve => {
+                self.preserved.push((result.len(), escape.unit));
+                result.push(char::REPLACEMENT_CHARACTER);
+                Ok(())
+            }
+        }
+    }
+
+    /// `result` as WTF-8, with the preserved surrogates put back in place
+    /// of their U+FFFD. Both take three bytes.
+    fn preserved_wtf8(&self, result: &str) -> Option<Vec<u8>> {
+        if self.preserved.is_empty() {
+            return None;
+        }
+        let mut bytes = result.as_bytes().to_vec();
+        for &(at, unit) in &self.preserved {
+            bytes[at] = 0xE0 | (unit >> 12) as u8;
+            bytes[at + 1] = 0x80 | (unit >> 6 & 0x3F) as u8;
+            bytes[at + 2] = 0x80 | (unit & 0x3F) as u8;
         }
-        false
+        Some(bytes)
     }
 
-    fn read_identifier_from(&mut self, start_position: usize) -> Token {
+    /// reads a bare word, which is only valid as `true`, `false` or `null`
+    fn read_keyword(&mut self) -> Token<'a> {
+        let start = self.offset;
+        let mut word = String::from(self.ch.unwrap_or_default() as char);
         while let Some(c) = self.peek() {
-            if c.is_alphanumeric() || c == '_' {
+            let c = if c.is_ascii_alphanumeric() || c == b'_' {
                 self.read_char();
+                c as char
+            } else if !c.is_ascii() {
+                // non-ASCII letters still belong to the word, so `trué`
+                // is reported whole
+                self.read_char();
+                match self.read_utf8_char() {
+                    Ok(c) => c,
+                    Err(t) => return t,
+                }
             } else {
                 break;
+            };
+            // no keyword is that long, the rest is only skipped
+            if word.len() < MAX_INVALID_TEXT {
+                word.push(c);
             }
         }
+        if let Some(failure) = self.cut_short() {
+            return failure;
+        }
 
-        let identifier = self.input[start_position..=self.position]
-            .iter()
-            .collect::<String>();
-
-        Token::InvalidKeyword(identifier)
+        match word.as_str() {
+            "true" => Token::BooleanLiteral(true),
+            "false" => Token::BooleanLiteral(false),
+            "null" => Token::NullLiteral,
+            "NaN" if self.nan_infinity => Token::NumberLiteral(Cow::Borrowed("NaN")),
+            "Infinity" if self.nan_infinity => Token::NumberLiteral(Cow::Borrowed("Infinity")),
+            _ => Token::InvalidKeyword(invalid_text(&word, self.offset + 1 - start)),
+        }
     }
 
-    fn validate_keyword(&mut self, keyword: &str) -> bool {
-        for c in keyword[1..].chars() {
-            if !self.read_char_is_matched(c) {
-                return false;
+    /// reads `-Infinity`, with the current byte on the minus sign
+    fn read_negative_infinity(&mut self) -> Token<'a> {
+        self.read_char();
+        match self.read_keyword() {
+            Token::NumberLiteral(n) if n == "Infinity" => {
+                Token::NumberLiteral(Cow::Borrowed("-Infinity"))
             }
+            Token::InvalidKeyword(word) => Token::InvalidNumber(format!("-{word}")),
+            other => other,
         }
-        true
     }
 
-    fn read_keyword(&mut self, keyword: &str, token: Token) -> Token {
-        let start_position = self.position;
-
-        if self.validate_keyword(keyword) {
-            if let Some(next_char) = self.peek() {
-                if next_char.is_alphanumeric() || next_char == '_' {
-                    self.read_identifier_from(start_position)
-                } else {
-                    token
+    /// skips whitespace, and comments when they are allowed. Fails on an
+    /// unterminated comment or invalid UTF-8 inside a comment.
+    fn skip_whitespace(&mut self) -> Result<(), Token<'a>> {
+        while let Some(c) = self.ch {
+            match c {
+                b' ' | b'\n' | b'\t' | b'\r' => self.read_char(),
+                b'/' if self.comments && self.peek() == Some(b'/') => {
+                    while self.ch.is_some_and(|c| c != b'\n') {
+                        self.skip_comment_char()?;
+                    }
                 }
-            } else {
-                token
+                b'/' if self.comments && self.peek() == Some(b'*') => {
+                    let start = self.here();
+                    self.read_char();
+                    self.read_char();
+                    loop {
+                        match self.ch {
+                            None => {
+                                self.token_span = start;
+                                let failure = self.failure.clone();
+                                return Err(failure.unwrap_or(Token::UnterminatedComment));
+                            }
+                            Some(b'*') if self.peek() == Some(b'/') => {
+                                self.read_char();
+                                self.read_char();
+                                break;
+                            }
+                            Some(_) => self.skip_comment_char()?,
+                        }
+                    }
+                }
+                _ => break,
             }
-        } else {
-            self.read_identifier_from(start_position)
         }
+        Ok(())
     }
 
-    fn skip_whitespace(&mut self) {
-        while let Some(c) = self.ch {
-            if c == ' ' || c == '\n' || c == '\t' || c == '\r' {
-                self.read_char();
-            } else {
-                break;
-            }
+    /// moves past one char of a comment, checking that it is valid UTF-8
+    fn skip_comment_char(&mut self) -> Result<(), Token<'a>> {
+        if !self.ch.unwrap_or_default().is_ascii() {
+            self.read_utf8_char()?;
         }
+        self.read_char();
+        Ok(())
+    }
+}
+
+/// the text of an invalid token of `len` bytes for its error, cut to
+/// `MAX_INVALID_TEXT` bytes and marked with `…` when it is longer
+fn invalid_text(text: &str, len: usize) -> String {
+    let mut end = text.len().min(MAX_INVALID_TEXT);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    if end == len {
+        return text.to_string();
+    }
+    format!("{}…", &text[..end])
+}
+
+/// whether `b` continues a multi-byte UTF-8 sequence
+fn is_continuation(b: u8) -> bool {
+    b & 0xC0 == 0x80
+}
+
+/// checks `text` against the RFC 8259 number grammar:
+/// `-? (0 | [1-9][0-9]*) (. [0-9]+)? ([eE] [+-]? [0-9]+)?`
+pub(crate) fn is_valid_number(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    let digits_from = |i: usize| bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+
+    if bytes.get(i) == Some(&b'-') {
+        i += 1;
     }
+
+    match bytes.get(i) {
+        Some(b'0') => i += 1,
+        Some(b'1'..=b'9') => i += digits_from(i),
+        _ => return false,
+    }
+
+    if bytes.get(i) == Some(&b'.') {
+        i += 1;
+        let digits = digits_from(i);
+        if digits == 0 {
+            return false;
+        }
+        i += digits;
+    }
+
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        i += 1;
+        if matches!(bytes.get(i), Some(b'+' | b'-')) {
+            i += 1;
+        }
+        let digits = digits_from(i);
+        if digits == 0 {
+            return false;
+        }
+        i += digits;
+    }
+
+    i == bytes.len()
 }
@@ -1,3 +1,14 @@
+#[cfg(feature = "arbitrary_precision")]
+pub mod decimal;
+pub mod error;
+pub mod event;
+pub mod float;
 pub mod lexer;
+pub mod limits;
+pub mod map;
+pub mod number;
+pub mod options;
 pub mod parser;
+mod source;
 pub mod token;
+pub mod visitor;
@@ -0,0 +1,50 @@
+/// Nesting allowed by default, deep enough for any real document and far
+/// from what it takes to overflow the stack of code that recurses over a
+/// `JsonValue`.
+pub const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Bounds on what a document may contain, so hostile input can't exhaust
+/// memory. Each limit is `None` for no limit and fails with its own
+/// `ParseErrorKind` as soon as it is crossed, before the offending value is
+/// read whole.
+///
+/// Only nesting is limited by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// bytes of input, whitespace included
+    pub max_input_bytes: Option<usize>,
+
+    /// bytes of a single string or key, as written in the input
+    pub max_string_bytes: Option<usize>,
+
+    /// digits of a single number, exponent included. The number also
+    /// fails once it is longer than that and the four signs, point and `e`
+    /// it may have, so `1eeee…` is not read whole either.
+    pub max_number_digits: Option<usize>,
+
+    /// members of a single object
+    pub max_object_keys: Option<usize>,
+
+    /// elements of a single array
+    pub max_array_elements: Option<usize>,
+
+    /// values in the whole document, containers included
+    pub max_nodes: Option<usize>,
+
+    /// containers open at once
+    pub max_depth: Option<usize>,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            max_input_bytes: None,
+            max_string_bytes: None,
+            max_number_digits: None,
+            max_object_keys: None,
+            max_array_elements: None,
+            max_nodes: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+        }
+    }
+}
@@ -63,7 +63,7 @@ fn main() {
       }
     ]"#,
     );
-    let p = Parser::new(input.chars().collect());
+    let p = Parser::new(&input);
     for i in p {
         println!("{}", i.unwrap())
     }
@@ -0,0 +1,516 @@
+#[cfg(feature = "sorted_keys")]
+use std::collections::{btree_map, BTreeMap};
+#[cfg(not(feature = "sorted_keys"))]
+use std::{collections::hash_map::RandomState, hash::BuildHasher};
+
+use crate::parser::JsonValue;
+
+/// The key/value pairs of a JSON object.
+///
+/// Keys keep the order in which they were inserted, so a document prints back
+/// the way it was written. With the `sorted_keys` feature they are kept in
+/// lexicographic order instead, in a `BTreeMap`.
+#[derive(Debug, Clone, Default)]
+pub struct Map {
+    #[cfg(not(feature = "sorted_keys"))]
+    entries: Vec<(String, JsonValue)>,
+
+    /// position of the first pair of each key in `entries`
+    #[cfg(not(feature = "sorted_keys"))]
+    index: Index,
+
+    /// the first value of each key
+    #[cfg(feature = "sorted_keys")]
+    entries: BTreeMap<String, JsonValue>,
+
+    /// the further values of keys added more than once with `append`
+    #[cfg(feature = "sorted_keys")]
+    repeated: BTreeMap<String, Vec<JsonValue>>,
+}
+
+impl Map {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// the place of `key` in the map, to read or insert its value in one
+    /// lookup
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
+        Entry {
+            map: self,
+            key: key.into(),
+        }
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        self.iter().map(|(_, v)| v)
+    }
+
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut JsonValue> {
+        self.iter_mut().map(|(_, v)| v)
+    }
+}
+
+#[cfg(not(feature = "sorted_keys"))]
+impl Map {
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.index
+            .find(&self.entries, key)
+            .map(|i| &self.entries[i].1)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.index
+            .find(&self.entries, key)
+            .map(|i| &mut self.entries[i].1)
+    }
+
+    /// inserts a key/value pair. An existing key keeps its position and the
+    /// previous value is returned.
+    pub fn insert(&mut self, key: String, value: JsonValue) -> Option<JsonValue> {
+        match self.index.find(&self.entries, &key) {
+            Some(i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.push_new(key, value);
+                None
+            }
+        }
+    }
+
+    /// adds a pair even if the key is already present, for objects that
+    /// must keep every occurrence of a key. `get` still returns the first
+    /// value, `get_all` returns all of them.
+    pub fn append(&mut self, key: String, value: JsonValue) {
+        if self.index.find(&self.entries, &key).is_none() {
+            self.push_new(key, value);
+        } else {
+            self.entries.push((key, value));
+        }
+    }
+
+    /// removes `key`, every occurrence of it, and returns the value `get`
+    /// would have returned. The other keys keep their order.
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        let i = self.index.find(&self.entries, key)?;
+        if !self.has_duplicates() {
+            self.index.remove(key, i);
+            return Some(self.entries.remove(i).1);
+        }
+
+        let (_, value) = self.entries.remove(i);
+        self.entries.retain(|(k, _)| k != key);
+        self.index.rebuild(&self.entries);
+        Some(value)
+    }
+
+    /// keeps only the pairs for which `keep` returns true
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &mut JsonValue) -> bool) {
+        self.entries.retain_mut(|(k, v)| keep(k, v));
+        self.index.rebuild(&self.entries);
+    }
+
+    /// puts the keys in lexicographic order, once. Repeated keys keep the
+    /// order of their values. Keys added later go at the end, unless the
+    /// `sorted_keys` feature keeps them sorted anyway.
+    pub fn sort_keys(&mut self) {
+        self.entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        self.index.rebuild(&self.entries);
+    }
+
+    /// every value stored under `key`, in the order they were added
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a JsonValue> {
+        let from = self
+            .index
+            .find(&self.entries, key)
+            .unwrap_or(self.entries.len());
+        self.entries[from..]
+            .iter()
+            .filter(move |(k, _)| k == key)
+            .map(|(_, v)| v)
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut JsonValue)> {
+        self.entries.iter_mut().map(|(k, v)| (&*k, v))
+    }
+
+    fn has_duplicates(&self) -> bool {
+        self.index.len != self.entries.len()
+    }
+
+    fn get_or_insert_with(
+        &mut self,
+        key: String,
+        value: impl FnOnce() -> JsonValue,
+    ) -> &mut JsonValue {
+        let i = match self.index.find(&self.entries, &key) {
+            Some(i) => i,
+            None => {
+                self.push_new(key, value());
+                self.entries.len() - 1
+            }
+        };
+        &mut self.entries[i].1
+    }
+
+    /// adds a key that is not in the map yet
+    fn push_new(&mut self, key: String, value: JsonValue) {
+        self.index.insert(&key, self.entries.len());
+        self.entries.push((key, value));
+    }
+}
+
+#[cfg(feature = "sorted_keys")]
+impl Map {
+    pub fn len(&self) -> usize {
+        self.entries.len() + self.repeated.values().map(Vec::len).sum::<usize>()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.entries.get(key)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.entries.get_mut(key)
+    }
+
+    /// inserts a key/value pair, returning the previous value of the key
+    pub fn insert(&mut self, key: String, value: JsonValue) -> Option<JsonValue> {
+        self.entries.insert(key, value)
+    }
+
+    /// adds a pair even if the key is already present, for objects that
+    /// must keep every occurrence of a key. `get` still returns the first
+    /// value, `get_all` returns all of them.
+    pub fn append(&mut self, key: String, value: JsonValue) {
+        if self.entries.contains_key(&key) {
+            self.repeated.entry(key).or_default().push(value);
+            return;
+        }
+        self.entries.insert(key, value);
+    }
+
+    /// removes `key`, every occurrence of it, and returns the value `get`
+    /// would have returned
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        self.repeated.remove(key);
+        self.entries.remove(key)
+    }
+
+    /// keeps only the pairs for which `keep` returns true
+    pub fn retain(&mut self, mut keep: impl FnMut(&str, &mut JsonValue) -> bool) {
+        if self.repeated.is_empty() {
+            self.entries.retain(|k, v| keep(k, v));
+            return;
+        }
+        for (k, mut v) in std::mem::take(self) {
+            if keep(&k, &mut v) {
+                self.append(k, v);
+            }
+        }
+    }
+
+    /// does nothing, the keys are always sorted
+    pub fn sort_keys(&mut self) {}
+
+    /// every value stored under `key`, in the order they were added
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a JsonValue> {
+        self.entries
+            .get(key)
+            .into_iter()
+            .chain(self.repeated.get(key).into_iter().flatten())
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter {
+            inner: self.entries.iter(),
+            repeated: self.repeated.iter().peekable(),
+            pending: None,
+        }
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&String, &mut JsonValue)> {
+        let mut repeated = self.repeated.iter_mut().peekable();
+        self.entries.iter_mut().flat_map(move |(k, v)| {
+            let more = repeated
+                .next_if(|(rk, _)| *rk == k)
+                .map(|(_, values)| values);
+            std::iter::once((k, v)).chain(more.into_iter().flatten().map(move |v| (k, v)))
+        })
+    }
+
+    fn has_duplicates(&self) -> bool {
+        !self.repeated.is_empty()
+    }
+
+    fn get_or_insert_with(
+        &mut self,
+        key: String,
+        value: impl FnOnce() -> JsonValue,
+    ) -> &mut JsonValue {
+        self.entries.entry(key).or_insert_with(value)
+    }
+}
+
+/// Two objects are equal when they hold the same pairs, in any order.
+/// Values of a repeated key must also appear in the same order.
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        if self.len() != other.len() {
+            return false;
+        }
+        if !self.has_duplicates() && !other.has_duplicates() {
+            return self.iter().all(|(k, v)| other.get(k) == Some(v));
+        }
+        self.iter()
+            .all(|(k, _)| self.get_all(k).eq(other.get_all(k)))
+    }
+}
+
+impl FromIterator<(String, JsonValue)> for Map {
+    fn from_iter<T: IntoIterator<Item = (String, JsonValue)>>(iter: T) -> Self {
+        let mut map = Map::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+/// A key of a `Map` that may or may not hold a value yet, see `Map::entry`.
+pub struct Entry<'a> {
+    map: &'a mut Map,
+    key: String,
+}
+
+impl<'a> Entry<'a> {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn or_insert(self, value: JsonValue) -> &'a mut JsonValue {
+        self.or_insert_with(|| value)
+    }
+
+    /// the value of the key, calling `value` to insert one if it has none
+    pub fn or_insert_with(self, value: impl FnOnce() -> JsonValue) -> &'a mut JsonValue {
+        self.map.get_or_insert_with(self.key, value)
+    }
+}
+
+pub struct Iter<'a> {
+    #[cfg(not(feature = "sorted_keys"))]
+    inner: std::slice::Iter<'a, (String, JsonValue)>,
+
+    #[cfg(feature = "sorted_keys")]
+    inner: btree_map::Iter<'a, String, JsonValue>,
+
+    #[cfg(feature = "sorted_keys")]
+    repeated: std::iter::Peekable<btree_map::Iter<'a, String, Vec<JsonValue>>>,
+
+    /// the further values of the key returned last
+    #[cfg(feature = "sorted_keys")]
+    pending: Option<(&'a String, std::slice::Iter<'a, JsonValue>)>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a JsonValue);
+
+    #[cfg(not(feature = "sorted_keys"))]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+
+    #[cfg(feature = "sorted_keys")]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some((k, values)) = &mut self.pending {
+            if let Some(v) = values.next() {
+                return Some((*k, v));
+            }
+            self.pending = None;
+        }
+
+        let (k, v) = self.inner.next()?;
+        if let Some((_, values)) = self.repeated.next_if(|(rk, _)| *rk == k) {
+            self.pending = Some((k, values.iter()));
+        }
+        Some((k, v))
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a String, &'a JsonValue);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (String, JsonValue);
+    type IntoIter = std::vec::IntoIter<(String, JsonValue)>;
+
+    #[cfg(not(feature = "sorted_keys"))]
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.into_iter()
+    }
+
+    #[cfg(feature = "sorted_keys")]
+    fn into_iter(self) -> Self::IntoIter {
+        let mut repeated = self.repeated;
+        let mut pairs = Vec::with_capacity(self.entries.len());
+        for (k, v) in self.entries {
+            match repeated.remove(&k) {
+                Some(values) => {
+                    pairs.push((k.clone(), v));
+                    pairs.extend(values.into_iter().map(|v| (k.clone(), v)));
+                }
+                None => pairs.push((k, v)),
+            }
+        }
+        pairs.into_iter()
+    }
+}
+
+/// A hash table of positions in `Map::entries`, so that each key is only
+/// stored once, in its pair. Open addressing with linear probing, kept at
+/// most half full.
+#[cfg(not(feature = "sorted_keys"))]
+#[derive(Debug, Clone, Default)]
+struct Index {
+    slots: Vec<Slot>,
+
+    /// number of keys in `slots`
+    len: usize,
+
+    hasher: RandomState,
+}
+
+#[cfg(not(feature = "sorted_keys"))]
+#[derive(Debug, Clone, Copy)]
+struct Slot {
+    /// hash of the key, kept to grow the table without the keys
+    hash: u64,
+
+    /// position of the pair in `entries`, `EMPTY` for a free slot
+    pos: usize,
+}
+
+#[cfg(not(feature = "sorted_keys"))]
+const EMPTY: Slot = Slot {
+    hash: 0,
+    pos: usize::MAX,
+};
+
+#[cfg(not(feature = "sorted_keys"))]
+impl Index {
+    fn find(&self, entries: &[(String, JsonValue)], key: &str) -> Option<usize> {
+        if self.slots.is_empty() {
+            return None;
+        }
+        let hash = self.hasher.hash_one(key);
+        let mask = self.slots.len() - 1;
+        let mut i = hash as usize & mask;
+        loop {
+            let slot = self.slots[i];
+            if slot.pos == EMPTY.pos {
+                return None;
+            }
+            if slot.hash == hash && entries[slot.pos].0 == key {
+                return Some(slot.pos);
+            }
+            i = (i + 1) & mask;
+        }
+    }
+
+    /// adds a key that is not in the table yet
+    fn insert(&mut self, key: &str, pos: usize) {
+        if (self.len + 1) * 2 > self.slots.len() {
+            let capacity = (self.slots.len() * 2).max(8);
+            let old = std::mem::replace(&mut self.slots, vec![EMPTY; capacity]);
+            for slot in old.into_iter().filter(|s| s.pos != EMPTY.pos) {
+                self.place(slot);
+            }
+        }
+        let hash = self.hasher.hash_one(key);
+        self.place(Slot { hash, pos });
+        self.len += 1;
+    }
+
+    /// drops `key`, found at `pos`, and shifts the positions after it down
+    /// by one, as `entries` will be once its pair is removed
+    fn remove(&mut self, key: &str, pos: usize) {
+        let mask = self.slots.len() - 1;
+        let mut hole = self.hasher.hash_one(key) as usize & mask;
+        while self.slots[hole].pos != pos {
+            hole = (hole + 1) & mask;
+        }
+
+        // move back the slots that probed past the hole, so lookups still
+        // reach them
+        let mut i = hole;
+        loop {
+            i = (i + 1) & mask;
+            let slot = self.slots[i];
+            if slot.pos == EMPTY.pos {
+                break;
+            }
+            let home = slot.hash as usize & mask;
+            if i.wrapping_sub(home) & mask >= i.wrapping_sub(hole) & mask {
+                self.slots[hole] = slot;
+                hole = i;
+            }
+        }
+        self.slots[hole] = EMPTY;
+        self.len -= 1;
+
+        for slot in &mut self.slots {
+            if slot.pos != EMPTY.pos && slot.pos > pos {
+                slot.pos -= 1;
+            }
+        }
+    }
+
+    fn place(&mut self, slot: Slot) {
+        let mask = self.slots.len() - 1;
+        let mut i = slot.hash as usize & mask;
+        while self.slots[i].pos != EMPTY.pos {
+            i = (i + 1) & mask;
+        }
+        self.slots[i] = slot;
+    }
+
+    /// indexes `entries` again after pairs moved, pointing at the first
+    /// occurrence of each key
+    fn rebuild(&mut self, entries: &[(String, JsonValue)]) {
+        self.slots.fill(EMPTY);
+        self.len = 0;
+        for (pos, (k, _)) in entries.iter().enumerate() {
+            if self.find(entries, k).is_none() {
+                self.insert(k, pos);
+            }
+        }
+    }
+}
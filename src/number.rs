@@ -0,0 +1,179 @@
+use std::{fmt::Display, str::FromStr};
+
+#[cfg(feature = "arbitrary_precision")]
+use crate::decimal::Decimal;
+use crate::{float, lexer::is_valid_number};
+
+/// Why a `JsonNumber` could not be converted or created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberError {
+    /// the number has a fraction or an exponent, or is `NaN` or `Infinity`
+    NotAnInteger,
+    /// the number does not fit in the requested type
+    OutOfRange,
+    /// the text is not a number in JSON syntax
+    Invalid,
+}
+
+impl Display for NumberError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotAnInteger => write!(f, "number is not an integer"),
+            Self::OutOfRange => write!(f, "number out of range"),
+            Self::Invalid => write!(f, "invalid number"),
+        }
+    }
+}
+
+impl std::error::Error for NumberError {}
+
+/// A JSON number. It keeps the text it was read from, so even numbers that
+/// fit no Rust type print back unchanged, and converts to Rust numbers on
+/// demand.
+///
+/// Two numbers are equal when they are written the same way: `1.0` and `1`
+/// are different numbers here, even if they have the same value.
+///
+/// With the `arbitrary_precision` feature the text is the canonical form of
+/// a `Decimal` instead, so numbers are equal when their values are, and
+/// conversions are exact: `1.5e1` is the integer `15`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JsonNumber {
+    text: String,
+}
+
+impl JsonNumber {
+    /// wraps text that the lexer already checked
+    pub(crate) fn from_lexeme(text: String) -> Self {
+        // `NaN`, `Infinity` and exponents beyond `i64` stay as written
+        #[cfg(feature = "arbitrary_precision")]
+        if let Ok(decimal) = text.parse::<Decimal>() {
+            return Self::from(decimal);
+        }
+        Self { text }
+    }
+
+    /// the number as written in the input, or in canonical form with the
+    /// `arbitrary_precision` feature
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// whether the number is written without fraction or exponent, e.g.
+    /// `-12` but not `1.0` or `1e3`
+    #[cfg(not(feature = "arbitrary_precision"))]
+    pub fn is_integer(&self) -> bool {
+        self.text.bytes().all(|b| b.is_ascii_digit() || b == b'-')
+    }
+
+    /// whether the value has no fractional part, e.g. `-12`, `1.0` and
+    /// `1e3` but not `1.5`
+    #[cfg(feature = "arbitrary_precision")]
+    pub fn is_integer(&self) -> bool {
+        self.as_decimal().is_ok_and(|d| d.is_integer())
+    }
+
+    pub fn is_float(&self) -> bool {
+        !self.is_integer()
+    }
+
+    pub fn as_i64(&self) -> Result<i64, NumberError> {
+        self.as_i128()?
+            .try_into()
+            .map_err(|_| NumberError::OutOfRange)
+    }
+
+    pub fn as_u64(&self) -> Result<u64, NumberError> {
+        self.as_i128()?
+            .try_into()
+            .map_err(|_| NumberError::OutOfRange)
+    }
+
+    #[cfg(not(feature = "arbitrary_precision"))]
+    pub fn as_i128(&self) -> Result<i128, NumberError> {
+        if !self.is_integer() {
+            return Err(NumberError::NotAnInteger);
+        }
+        self.text.parse().map_err(|_| NumberError::OutOfRange)
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    pub fn as_i128(&self) -> Result<i128, NumberError> {
+        match self.as_decimal() {
+            Err(NumberError::Invalid) => Err(NumberError::NotAnInteger),
+            decimal => decimal?.to_i128(),
+        }
+    }
+
+    /// the exact value. `Invalid` for `NaN` and infinities, `OutOfRange`
+    /// for exponents beyond `i64`.
+    #[cfg(feature = "arbitrary_precision")]
+    pub fn as_decimal(&self) -> Result<Decimal, NumberError> {
+        self.text.parse()
+    }
+
+    /// the nearest `f64`. Precision beyond what an `f64` holds is lost
+    /// silently, but a number too large for it is `OutOfRange`.
+    pub fn as_f64(&self) -> Result<f64, NumberError> {
+        match self.text.as_str() {
+            "NaN" => Ok(f64::NAN),
+            "Infinity" => Ok(f64::INFINITY),
+            "-Infinity" => Ok(f64::NEG_INFINITY),
+            text => match float::parse_f64(text) {
+                Some(f) if f.is_infinite() => Err(NumberError::OutOfRange),
+                Some(f) => Ok(f),
+                None => Err(NumberError::Invalid),
+            },
+        }
+    }
+
+    /// the shortest number that reads back as `f`. `None` for `NaN` and
+    /// infinities, which JSON can't hold.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        f.is_finite()
+            .then(|| Self::from_lexeme(float::format_f64(f)))
+    }
+}
+
+#[cfg(feature = "arbitrary_precision")]
+impl From<Decimal> for JsonNumber {
+    fn from(decimal: Decimal) -> Self {
+        Self {
+            text: decimal.to_string(),
+        }
+    }
+}
+
+impl Display for JsonNumber {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.text)
+    }
+}
+
+/// Accepts the JSON number grammar, plus `NaN`, `Infinity` and `-Infinity`
+/// that `ParserOptions::allow_nan_infinity` lets through.
+impl FromStr for JsonNumber {
+    type Err = NumberError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if is_valid_number(s) || matches!(s, "NaN" | "Infinity" | "-Infinity") {
+            Ok(Self::from_lexeme(s.to_string()))
+        } else {
+            Err(NumberError::Invalid)
+        }
+    }
+}
+
+macro_rules! impl_from_integer {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for JsonNumber {
+                fn from(n: $t) -> Self {
+                    Self::from_lexeme(n.to_string())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_integer!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
@@ -0,0 +1,117 @@
+use crate::{lexer::LoneSurrogatePolicy, limits::Limits, parser::DuplicateKeyPolicy};
+
+/// How the `Parser` stores the numbers it reads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NumberRepresentation {
+    /// keep each number exactly as written
+    #[default]
+    Exact,
+    /// round each number to the nearest `f64`, the way JavaScript reads
+    /// JSON. Integers of magnitude below 2^53, which an `f64` holds
+    /// exactly, and numbers beyond its range are kept as written.
+    F64,
+}
+
+/// Every setting of the lexer and the parser in one place, built once and
+/// applied with `Parser::with_options`. The default is strict RFC 8259.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParserOptions {
+    pub(crate) comments: bool,
+    pub(crate) trailing_commas: bool,
+    pub(crate) duplicate_keys: DuplicateKeyPolicy,
+    pub(crate) scalar_root: bool,
+    pub(crate) limits: Limits,
+    pub(crate) nan_infinity: bool,
+    pub(crate) single_quotes: bool,
+    pub(crate) numbers: NumberRepresentation,
+    pub(crate) lone_surrogates: LoneSurrogatePolicy,
+}
+
+impl Default for ParserOptions {
+    fn default() -> Self {
+        Self {
+            comments: false,
+            trailing_commas: false,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            scalar_root: true,
+            limits: Limits::default(),
+            nan_infinity: false,
+            single_quotes: false,
+            numbers: NumberRepresentation::default(),
+            lone_surrogates: LoneSurrogatePolicy::default(),
+        }
+    }
+}
+
+impl ParserOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// everything a hand-written file might contain: comments, trailing
+    /// commas, single quotes, `NaN` and `Infinity`
+    pub fn lenient() -> Self {
+        Self::new()
+            .allow_comments(true)
+            .allow_trailing_commas(true)
+            .allow_single_quotes(true)
+            .allow_nan_infinity(true)
+    }
+
+    /// skip `// line` and `/* block */` comments like whitespace
+    pub fn allow_comments(mut self, allow: bool) -> Self {
+        self.comments = allow;
+        self
+    }
+
+    /// accept `[1, 2,]` and `{"a": 1,}`
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.trailing_commas = allow;
+        self
+    }
+
+    pub fn with_duplicate_key_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// accept a string, number, boolean or null as the root value, on by
+    /// default as RFC 8259 allows it
+    pub fn allow_scalar_root(mut self, allow: bool) -> Self {
+        self.scalar_root = allow;
+        self
+    }
+
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// shorthand for changing only `Limits::max_depth`
+    pub fn with_max_depth(mut self, max: Option<usize>) -> Self {
+        self.limits.max_depth = max;
+        self
+    }
+
+    /// read `NaN`, `Infinity` and `-Infinity` as numbers
+    pub fn allow_nan_infinity(mut self, allow: bool) -> Self {
+        self.nan_infinity = allow;
+        self
+    }
+
+    /// accept strings in single quotes, and `\'` escapes
+    pub fn allow_single_quotes(mut self, allow: bool) -> Self {
+        self.single_quotes = allow;
+        self
+    }
+
+    pub fn with_number_representation(mut self, numbers: NumberRepresentation) -> Self {
+        self.numbers = numbers;
+        self
+    }
+
+    pub fn with_lone_surrogate_policy(mut self, policy: LoneSurrogatePolicy) -> Self {
+        self.lone_surrogates = policy;
+        self
+    }
+}
@@ -1,13 +1,31 @@
-use std::{collections::HashMap, fmt::Display, iter::Peekable};
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    io::Read,
+    ops::{Index, IndexMut},
+};
 
-use crate::{lexer::Lexer, token::Token};
+use crate::{
+    error::{ParseError, ParseErrorKind},
+    event::{Event, EventReader},
+    float,
+    lexer::LoneSurrogatePolicy,
+    limits::Limits,
+    map::{Entry, Map},
+    number::JsonNumber,
+    options::{NumberRepresentation, ParserOptions},
+    token::Span,
+    visitor::{JsonVisitor, VisitControl},
+};
 
+#[derive(Debug, Clone, PartialEq, Default)]
 pub enum JsonValue {
-    Object(HashMap<String, JsonValue>),
+    Object(Map),
     Array(Vec<JsonValue>),
     String(String),
-    Number(String),
+    Number(JsonNumber),
     Boolean(bool),
+    #[default]
     Null,
 }
 
@@ -51,202 +69,843 @@ impl Display for JsonValue {
     }
 }
 
-pub struct Parser {
-    pub lexer: Peekable<Lexer>,
-    started: bool,
+/// what indexing returns for a missing key or element
+static NULL: JsonValue = JsonValue::Null;
+
+impl JsonValue {
+    /// the value of `key` if this is an object that has it
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        self.as_object()?.get(key)
+    }
+
+    /// element `index` if this is an array long enough to have it
+    pub fn get_index(&self, index: usize) -> Option<&JsonValue> {
+        self.as_array()?.get(index)
+    }
+
+    /// the value at a JSON Pointer (RFC 6901) such as `/items/0/name`.
+    /// The empty pointer is the value itself.
+    pub fn pointer(&self, pointer: &str) -> Option<&JsonValue> {
+        let mut value = self;
+        for token in split_pointer(pointer)? {
+            value = match value {
+                Self::Object(map) => map.get(&token)?,
+                Self::Array(arr) => arr.get(parse_pointer_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut JsonValue> {
+        self.as_object_mut()?.get_mut(key)
+    }
+
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut JsonValue> {
+        self.as_array_mut()?.get_mut(index)
+    }
+
+    /// like `pointer`, to change the value in place
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut JsonValue> {
+        let mut value = self;
+        for token in split_pointer(pointer)? {
+            value = match value {
+                Self::Object(map) => map.get_mut(&token)?,
+                Self::Array(arr) => arr.get_mut(parse_pointer_index(&token)?)?,
+                _ => return None,
+            };
+        }
+        Some(value)
+    }
+
+    /// adds or replaces a member and returns the previous value. Like
+    /// assigning through an index, it turns `null` into an empty object
+    /// first and panics for other types.
+    pub fn insert(&mut self, key: impl Into<String>, value: JsonValue) -> Option<JsonValue> {
+        self.object_mut().insert(key.into(), value)
+    }
+
+    /// removes a member of an object, `None` if there is no such member
+    /// or this is not an object
+    pub fn remove(&mut self, key: &str) -> Option<JsonValue> {
+        self.as_object_mut()?.remove(key)
+    }
+
+    /// appends an element, turning `null` into an empty array first.
+    /// Panics for other types than arrays.
+    pub fn push(&mut self, value: JsonValue) {
+        if self.is_null() {
+            *self = Self::Array(vec![]);
+        }
+        match self {
+            Self::Array(arr) => arr.push(value),
+            _ => panic!("cannot push onto {}", self.type_name()),
+        }
+    }
+
+    /// `Map::entry` of this object, turning `null` into an empty object
+    /// first. Panics for other types.
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
+        self.object_mut().entry(key)
+    }
+
+    /// keeps only the members for which `keep` returns true. Does nothing
+    /// if this is not an object.
+    pub fn retain(&mut self, keep: impl FnMut(&str, &mut JsonValue) -> bool) {
+        if let Some(map) = self.as_object_mut() {
+            map.retain(keep);
+        }
+    }
+
+    /// sorts the keys of this object and of every object nested in it
+    pub fn sort_keys(&mut self) {
+        let mut pending = vec![self];
+        while let Some(value) = pending.pop() {
+            match value {
+                Self::Object(map) => {
+                    map.sort_keys();
+                    pending.extend(map.values_mut());
+                }
+                Self::Array(arr) => pending.extend(arr.iter_mut()),
+                _ => {}
+            }
+        }
+    }
+
+    /// the keys of an object, nothing for other types
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.as_object()
+            .into_iter()
+            .flat_map(|map| map.keys().map(String::as_str))
+    }
+
+    /// the values of an object or the elements of an array, nothing for
+    /// other types
+    pub fn values(&self) -> impl Iterator<Item = &JsonValue> {
+        let members = self.as_object().into_iter().flat_map(Map::values);
+        members.chain(self.as_array().into_iter().flatten())
+    }
+
+    /// the members of an object, nothing for other types
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &JsonValue)> {
+        self.as_object()
+            .into_iter()
+            .flat_map(|map| map.iter().map(|(k, v)| (k.as_str(), v)))
+    }
+
+    pub fn as_object(&self) -> Option<&Map> {
+        match self {
+            Self::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[JsonValue]> {
+        match self {
+            Self::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    pub fn as_object_mut(&mut self) -> Option<&mut Map> {
+        match self {
+            Self::Object(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    pub fn as_array_mut(&mut self) -> Option<&mut Vec<JsonValue>> {
+        match self {
+            Self::Array(arr) => Some(arr),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Self::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&JsonNumber> {
+        match self {
+            Self::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Self::Boolean(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// the JSON name of the type: `object`, `array`, `string`, `number`,
+    /// `boolean` or `null`
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Self::Object(_) => "object",
+            Self::Array(_) => "array",
+            Self::String(_) => "string",
+            Self::Number(_) => "number",
+            Self::Boolean(_) => "boolean",
+            Self::Null => "null",
+        }
+    }
+
+    /// moves the value out, leaving `null` in its place
+    pub fn take(&mut self) -> JsonValue {
+        std::mem::take(self)
+    }
+
+    /// puts `value` in place of this one and returns the old value
+    pub fn replace(&mut self, value: JsonValue) -> JsonValue {
+        std::mem::replace(self, value)
+    }
+
+    /// the map of an object, turning `null` into an empty one first
+    fn object_mut(&mut self) -> &mut Map {
+        if self.is_null() {
+            *self = Self::Object(Map::new());
+        }
+        match self {
+            Self::Object(map) => map,
+            _ => panic!("cannot use {} as an object", self.type_name()),
+        }
+    }
+}
+
+/// `value["key"]` is `null` when the value is not an object or has no such
+/// key, so lookups can be chained: `value["address"]["city"]`.
+impl Index<&str> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, key: &str) -> &JsonValue {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// `value[i]` is `null` when the value is not an array or is too short.
+impl Index<usize> for JsonValue {
+    type Output = JsonValue;
+
+    fn index(&self, index: usize) -> &JsonValue {
+        self.get_index(index).unwrap_or(&NULL)
+    }
+}
+
+/// Assigning to `value["key"]` adds the key if it is missing, and turns a
+/// `null` into an empty object first. Panics if the value is anything else
+/// than an object or `null`.
+impl IndexMut<&str> for JsonValue {
+    fn index_mut(&mut self, key: &str) -> &mut JsonValue {
+        self.object_mut().entry(key).or_insert(Self::Null)
+    }
+}
+
+/// Panics if the value is not an array or `index` is out of bounds, like
+/// indexing a `Vec`.
+impl IndexMut<usize> for JsonValue {
+    fn index_mut(&mut self, index: usize) -> &mut JsonValue {
+        match self {
+            Self::Array(arr) => {
+                let len = arr.len();
+                arr.get_mut(index).unwrap_or_else(|| {
+                    panic!("index {index} out of bounds for array of length {len}")
+                })
+            }
+            _ => panic!("cannot index into {} with {index}", self.type_name()),
+        }
+    }
+}
+
+/// A JSON value that borrows its keys, strings and numbers from the input
+/// whenever they have no escapes to decode, see `Parser::parse_borrowed`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum JsonValueRef<'a> {
+    /// members in the order they were written, repeated keys included
+    Object(Vec<(Cow<'a, str>, JsonValueRef<'a>)>),
+    Array(Vec<JsonValueRef<'a>>),
+    String(Cow<'a, str>),
+    Number(Cow<'a, str>),
+    Boolean(bool),
+    Null,
+}
+
+impl<'a> JsonValueRef<'a> {
+    /// the value of `key` if this is an object that has it. A repeated key
+    /// gives its last value, like `DuplicateKeyPolicy::LastWins`.
+    pub fn get(&self, key: &str) -> Option<&JsonValueRef<'a>> {
+        match self {
+            Self::Object(members) => members.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// copies the value out of the input. Repeated keys keep their last
+    /// value.
+    pub fn into_owned(self) -> JsonValue {
+        match self {
+            Self::Object(members) => JsonValue::Object(
+                members
+                    .into_iter()
+                    .map(|(k, v)| (k.into_owned(), v.into_owned()))
+                    .collect(),
+            ),
+            Self::Array(arr) => JsonValue::Array(arr.into_iter().map(Self::into_owned).collect()),
+            Self::String(s) => JsonValue::String(s.into_owned()),
+            Self::Number(n) => JsonValue::Number(JsonNumber::from_lexeme(n.into_owned())),
+            Self::Boolean(b) => JsonValue::Boolean(b),
+            Self::Null => JsonValue::Null,
+        }
+    }
+}
+
+/// What to do when an object repeats a key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateKeyPolicy {
+    /// reject the document with `ParseErrorKind::DuplicateKey`
+    Error,
+    /// keep the value of the first occurrence
+    FirstWins,
+    /// keep the value of the last occurrence, at the position of the first
+    #[default]
+    LastWins,
+    /// keep every occurrence, see `Map::get_all`
+    KeepAll,
+}
+
+pub struct Parser<'a> {
+    events: EventReader<'a>,
+
+    duplicate_keys: DuplicateKeyPolicy,
+
+    numbers: NumberRepresentation,
+
+    /// depth of the container whose items are being streamed, once open
+    stream_depth: Option<usize>,
+
     finished: bool,
 }
 
-// implemented only for array of objects
-impl Iterator for Parser {
-    type Item = Result<JsonValue, String>;
+/// Streams the elements of a root array one at a time, whatever their type,
+/// so the whole array never has to be in memory.
+impl Iterator for Parser<'_> {
+    type Item = Result<JsonValue, ParseError>;
     fn next(&mut self) -> Option<Self::Item> {
-        if self.finished {
-            return None;
-        }
+        self.stream_next(Event::StartArray, |p, event| p.build_value(event))
+    }
+}
 
-        if !self.started {
-            match self.lexer.next() {
-                Some(Token::LSquare) => {
-                    self.started = true;
-                }
-                None => {
-                    self.finished = true;
-                    return None;
-                }
-                _ => {
-                    self.finished = true;
-                    return Some(Err("Expected array start".into()));
-                }
+/// Streams the key/value pairs of a root object one at a time, see
+/// `Parser::into_entries`.
+pub struct Entries<'a> {
+    parser: Parser<'a>,
+}
+
+impl Iterator for Entries<'_> {
+    type Item = Result<(String, JsonValue), ParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.parser.stream_next(Event::StartObject, |p, event| {
+            let Event::Key(key) = event else {
+                return Err(p.unexpected(event));
+            };
+            let first = p.next_event()?;
+            let value = p.build_value(first)?;
+            Ok((key.into_owned(), value))
+        })
+    }
+}
+
+/// Streams the elements of an array nested inside the document, see
+/// `Parser::into_elements_at`.
+pub struct ElementsAt<'a> {
+    parser: Parser<'a>,
+    pointer: String,
+
+    /// whether the parser already moved to the array
+    found: bool,
+}
+
+impl Iterator for ElementsAt<'_> {
+    type Item = Result<JsonValue, ParseError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.found {
+            self.found = true;
+            let opened = self
+                .parser
+                .seek(&self.pointer)
+                .and_then(|event| self.parser.open_stream(event, Event::StartArray));
+            if let Err(e) = opened {
+                self.parser.finished = true;
+                return Some(Err(e));
             }
         }
+        self.parser.next()
+    }
+}
 
-        loop {
-            match self.lexer.next() {
-                Some(Token::LBrace) => match self.parse_object() {
-                    Ok(obj) => return Some(Ok(obj)),
-                    Err(e) => {
-                        self.finished = true;
-                        return Some(Err(e));
-                    }
-                },
-                Some(Token::RSquare) => {
-                    self.finished = true;
-                    return None;
-                }
-                Some(Token::Comma) => {
-                    continue;
-                }
-                None => {
-                    self.finished = true;
-                    return None;
-                }
-                _ => {
-                    self.finished = true;
-                    return Some(Err("unexpected token in array".into()));
+/// A container being filled by `Parser::build_value`.
+enum Partial {
+    Array(Vec<JsonValue>),
+    /// the object and the key of the member being read
+    Object(Map, Option<String>),
+}
+
+/// A container being filled by `Parser::build_borrowed`.
+enum PartialRef<'a> {
+    Array(Vec<JsonValueRef<'a>>),
+    /// the members so far and the key of the member being read
+    Object(Vec<(Cow<'a, str>, JsonValueRef<'a>)>, Option<Cow<'a, str>>),
+}
+
+/// splits a JSON Pointer into its unescaped reference tokens
+pub(crate) fn split_pointer(pointer: &str) -> Option<Vec<String>> {
+    if pointer.is_empty() {
+        return Some(vec![]);
+    }
+    pointer
+        .strip_prefix('/')?
+        .split('/')
+        .map(|token| {
+            let mut unescaped = String::with_capacity(token.len());
+            let mut chars = token.chars();
+            while let Some(c) = chars.next() {
+                match c {
+                    '~' => match chars.next() {
+                        Some('0') => unescaped.push('~'),
+                        Some('1') => unescaped.push('/'),
+                        _ => return None,
+                    },
+                    c => unescaped.push(c),
                 }
             }
-        }
+            Some(unescaped)
+        })
+        .collect()
+}
+
+/// reads a pointer token as an array index: digits without leading zeros
+pub(crate) fn parse_pointer_index(token: &str) -> Option<usize> {
+    if token.is_empty() || !token.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    if token.len() > 1 && token.starts_with('0') {
+        return None;
     }
+    token.parse().ok()
 }
 
-impl Parser {
-    pub fn new(input: Vec<char>) -> Self {
+impl<'a> Parser<'a> {
+    pub fn new(input: &'a str) -> Self {
+        Self::from_events(EventReader::new(input))
+    }
+
+    /// reads UTF-8 bytes in place, reporting invalid UTF-8 where it is found
+    pub fn from_slice(input: &'a [u8]) -> Self {
+        Self::from_events(EventReader::from_slice(input))
+    }
+
+    /// reads UTF-8 text from `reader` (a file, stdin, a socket, ...)
+    /// through a fixed-size buffer. Combined with the streaming iterators,
+    /// `visit` or `EventReader`, memory use stays constant whatever the
+    /// size of the input.
+    pub fn from_reader(reader: impl Read + 'a) -> Self {
+        Self::from_events(EventReader::from_reader(reader))
+    }
+
+    fn from_events(events: EventReader<'a>) -> Self {
         Self {
-            lexer: Lexer::new(input).peekable(),
-            started: false,
+            events,
+            duplicate_keys: DuplicateKeyPolicy::default(),
+            numbers: NumberRepresentation::default(),
+            stream_depth: None,
             finished: false,
         }
     }
 
-    pub fn parse(&mut self) -> Result<JsonValue, String> {
-        if let Some(token_to_parse) = self.lexer.next() {
-            match token_to_parse {
-                Token::LSquare => return self.parse_array(),
-                Token::LBrace => return self.parse_object(),
-                _ => return Err(format!("unexpected character {token_to_parse}")),
+    pub fn with_lone_surrogate_policy(mut self, policy: LoneSurrogatePolicy) -> Self {
+        self.events = self.events.with_lone_surrogate_policy(policy);
+        self
+    }
+
+    /// lenient mode for hand-edited files: accept `[1, 2,]` and `{"a": 1,}`
+    pub fn allow_trailing_commas(mut self, allow: bool) -> Self {
+        self.events = self.events.allow_trailing_commas(allow);
+        self
+    }
+
+    /// fails with `ParseErrorKind::DepthLimitExceeded` when containers are
+    /// nested more than `max` levels deep, `limits::DEFAULT_MAX_DEPTH` unless set.
+    /// Parsing never recurses, but dropping or printing a `JsonValue` does,
+    /// so only lift the limit (`None`) for trusted input.
+    pub fn with_max_depth(mut self, max: Option<usize>) -> Self {
+        self.events = self.events.with_max_depth(max);
+        self
+    }
+
+    /// applies a whole set of options at once, replacing any set before
+    pub fn with_options(mut self, options: &ParserOptions) -> Self {
+        self.events = self.events.with_options(options);
+        self.duplicate_keys = options.duplicate_keys;
+        self.numbers = options.numbers;
+        self
+    }
+
+    pub fn with_number_representation(mut self, numbers: NumberRepresentation) -> Self {
+        self.numbers = numbers;
+        self
+    }
+
+    /// bounds the size of the document, see `Limits`
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.events = self.events.with_limits(limits);
+        self
+    }
+
+    pub fn with_duplicate_key_policy(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self.events = self
+            .events
+            .reject_duplicate_keys(policy == DuplicateKeyPolicy::Error);
+        self
+    }
+
+    /// turns the parser into an iterator over the key/value pairs of a root
+    /// object. Pairs are yielded as they are read, so `DuplicateKeyPolicy`
+    /// can only reject repeated keys, not merge them.
+    pub fn into_entries(self) -> Entries<'a> {
+        Entries { parser: self }
+    }
+
+    /// turns the parser into an iterator over the elements of the array
+    /// found at `pointer`, a JSON Pointer (RFC 6901) such as `/data/items`.
+    /// Everything around that array is checked but skipped without being
+    /// built, so memory use does not depend on the size of the document.
+    pub fn into_elements_at(self, pointer: &str) -> ElementsAt<'a> {
+        ElementsAt {
+            parser: self,
+            pointer: pointer.to_string(),
+            found: false,
+        }
+    }
+
+    /// span of the token behind the last event read
+    pub fn span(&self) -> Span {
+        self.events.span()
+    }
+
+    /// parses a whole document: one JSON value of any type (object, array,
+    /// string, number, boolean or null) followed only by whitespace
+    pub fn parse(&mut self) -> Result<JsonValue, ParseError> {
+        let first = self.next_event()?;
+        let value = self.build_value(first)?;
+        self.events.expect_end()?;
+        Ok(value)
+    }
+
+    /// same as `parse`, but keys, strings and numbers borrow from the input
+    /// when they have no escapes, so most of them are never copied. Objects
+    /// keep every member as written; of the duplicate key policies only
+    /// `DuplicateKeyPolicy::Error` applies.
+    pub fn parse_borrowed(&mut self) -> Result<JsonValueRef<'a>, ParseError> {
+        let first = self.next_event()?;
+        let value = self.build_borrowed(first)?;
+        self.events.expect_end()?;
+        Ok(value)
+    }
+
+    /// parses the value at the start of the remaining input and leaves
+    /// whatever follows it unread. Returns the value and the byte offset
+    /// where the remainder starts; call it again to read the next value of
+    /// a stream of values packed back to back.
+    pub fn parse_prefix(&mut self) -> Result<(JsonValue, usize), ParseError> {
+        self.events.restart();
+        let first = self.next_event()?;
+        let value = self.build_value(first)?;
+        Ok((value, self.events.span().end))
+    }
+
+    /// walks a whole document and reports it to `visitor` as it is read,
+    /// without building any value. Returns `Ok(false)` if the visitor
+    /// aborted, in which case the rest of the input is left unread.
+    pub fn visit<V: JsonVisitor + ?Sized>(&mut self, visitor: &mut V) -> Result<bool, ParseError> {
+        while let Some(event) = self.events.next_event()? {
+            let control = match &event {
+                Event::StartObject => visitor.on_object_start(),
+                Event::EndObject => visitor.on_object_end(),
+                Event::StartArray => visitor.on_array_start(),
+                Event::EndArray => visitor.on_array_end(),
+                Event::Key(key) => visitor.on_key(key),
+                Event::String(s) => visitor.on_string(s),
+                Event::Number(n) => visitor.on_number(n),
+                Event::Bool(b) => visitor.on_bool(*b),
+                Event::Null => visitor.on_null(),
+            };
+
+            match control {
+                VisitControl::Continue => {}
+                VisitControl::Skip => match event {
+                    Event::Key(_) => self.events.skip_value()?,
+                    _ => self.events.skip_rest(&event)?,
+                },
+                VisitControl::Abort => return Ok(false),
             }
         }
-        Err("invaled EOF".into())
+        self.events.expect_end()?;
+        Ok(true)
+    }
+
+    /// checks whether only whitespace is left in the input
+    pub fn is_at_end(&mut self) -> bool {
+        self.events.is_at_end()
+    }
+
+    /// next event of a value that is not complete yet
+    fn next_event(&mut self) -> Result<Event<'a>, ParseError> {
+        match self.events.next_event()? {
+            Some(event) => Ok(event),
+            None => Err(self.events.error(ParseErrorKind::UnexpectedEof)),
+        }
     }
 
-    fn parse_array(&mut self) -> Result<JsonValue, String> {
-        let mut arr: Vec<JsonValue> = vec![];
+    /// builds the error for an event that does not fit where it was found
+    fn unexpected(&self, event: Event<'_>) -> ParseError {
+        self.events
+            .error(ParseErrorKind::UnexpectedToken(event.to_string()))
+    }
+
+    /// builds the value whose first event is `first`. Containers are kept on
+    /// an explicit stack, so deep documents do not recurse.
+    fn build_value(&mut self, first: Event<'a>) -> Result<JsonValue, ParseError> {
+        let mut stack: Vec<Partial> = vec![];
+        let mut event = first;
 
         loop {
-            if let Some(token_to_parse) = self.lexer.next() {
-                match token_to_parse {
-                    Token::StringLiteral(s) => {
-                        if !matches!(self.lexer.peek(), Some(Token::Comma) | Some(Token::RSquare)) {
-                            return Err("invalid token in array number".into());
-                        }
-                        arr.push(JsonValue::String(s));
+            let value = match event {
+                Event::StartObject => {
+                    stack.push(Partial::Object(Map::new(), None));
+                    None
+                }
+                Event::StartArray => {
+                    stack.push(Partial::Array(vec![]));
+                    None
+                }
+                Event::Key(key) => {
+                    if let Some(Partial::Object(_, pending)) = stack.last_mut() {
+                        *pending = Some(key.into_owned());
                     }
-                    Token::NumberLiteral(n) => {
-                        if !matches!(self.lexer.peek(), Some(Token::Comma) | Some(Token::RSquare)) {
-                            return Err("invalid token in array number".into());
-                        }
+                    None
+                }
+                Event::EndObject | Event::EndArray => match stack.pop() {
+                    Some(Partial::Object(obj, _)) => Some(JsonValue::Object(obj)),
+                    Some(Partial::Array(arr)) => Some(JsonValue::Array(arr)),
+                    None => return Err(self.unexpected(event)),
+                },
+                Event::String(s) => Some(JsonValue::String(s.into_owned())),
+                Event::Number(n) => {
+                    let n = JsonNumber::from_lexeme(self.number(n).into_owned());
+                    Some(JsonValue::Number(n))
+                }
+                Event::Bool(b) => Some(JsonValue::Boolean(b)),
+                Event::Null => Some(JsonValue::Null),
+            };
 
-                        arr.push(JsonValue::Number(n));
-                    }
-                    Token::BooleanLiteral(b) => {
-                        if !matches!(self.lexer.peek(), Some(Token::Comma) | Some(Token::RSquare)) {
-                            return Err("invalid token in array number".into());
-                        }
-                        arr.push(JsonValue::Boolean(b));
-                    }
-                    Token::NullLiteral => {
-                        if !matches!(self.lexer.peek(), Some(Token::Comma) | Some(Token::RSquare)) {
-                            return Err("invalid token in array number".into());
+            if let Some(value) = value {
+                match stack.last_mut() {
+                    None => return Ok(value),
+                    Some(Partial::Array(arr)) => arr.push(value),
+                    Some(Partial::Object(obj, key)) => {
+                        let key = key.take().unwrap_or_default();
+                        match self.duplicate_keys {
+                            DuplicateKeyPolicy::FirstWins if obj.contains_key(&key) => {}
+                            DuplicateKeyPolicy::KeepAll => obj.append(key, value),
+                            _ => {
+                                obj.insert(key, value);
+                            }
                         }
-                        arr.push(JsonValue::Null);
                     }
-                    Token::LBrace => {
-                        let obj = self.parse_object()?;
-                        arr.push(obj);
-                    }
-                    Token::RBrace => return Err("invalid character }".into()),
-                    Token::LSquare => {
-                        let arr2 = self.parse_array()?;
-                        arr.push(arr2);
-                    }
-                    Token::RSquare => {
-                        // TODO: Debo hacer algo mas?
-                        break;
-                    }
-                    Token::Comma => {
-                        // TODO: Debo validar que el siguiente sea un token valido?
-                        continue;
-                    }
-                    Token::Colon => return Err("illegal chararacter, colon in array".into()),
-                    Token::Illegal(c) => return Err(format!("illegal chararacter {c}")),
-                    Token::InvalidKeyword(key) => return Err(format!("invalid keyword {key}")),
-                    Token::EOF => return Err("unexpected EOF".into()),
                 }
             }
+            event = self.next_event()?;
         }
-
-        Ok(JsonValue::Array(arr))
     }
 
-    fn parse_object(&mut self) -> Result<JsonValue, String> {
-        let mut obj: HashMap<String, JsonValue> = HashMap::new();
-        let mut is_key: bool = true;
-        let mut key: String = "".into();
-        let mut value: Option<JsonValue> = None;
+    /// same as `build_value`, for a `JsonValueRef`
+    fn build_borrowed(&mut self, first: Event<'a>) -> Result<JsonValueRef<'a>, ParseError> {
+        let mut stack: Vec<PartialRef<'a>> = vec![];
+        let mut event = first;
 
         loop {
-            if let Some(token_to_parse) = self.lexer.next() {
-                match token_to_parse {
-                    Token::StringLiteral(s) => {
-                        if is_key {
-                            key = s;
-                        } else {
-                            value = Some(JsonValue::String(s));
-                        }
-                    }
-                    Token::NullLiteral => value = Some(JsonValue::Null),
-                    Token::NumberLiteral(n) => value = Some(JsonValue::Number(n)),
-                    Token::BooleanLiteral(b) => value = Some(JsonValue::Boolean(b)),
-                    Token::Colon => {
-                        is_key = false;
+            let value = match event {
+                Event::StartObject => {
+                    stack.push(PartialRef::Object(vec![], None));
+                    None
+                }
+                Event::StartArray => {
+                    stack.push(PartialRef::Array(vec![]));
+                    None
+                }
+                Event::Key(key) => {
+                    if let Some(PartialRef::Object(_, pending)) = stack.last_mut() {
+                        *pending = Some(key);
                     }
-                    Token::Comma => {
-                        if key.is_empty() || value.is_none() {
-                            return Err("empty key or value in comma statement".into());
-                        }
-                        let _ = obj.insert(key, value.unwrap());
-                        key = "".into();
-                        value = None;
-                        is_key = true;
+                    None
+                }
+                Event::EndObject | Event::EndArray => match stack.pop() {
+                    Some(PartialRef::Object(members, _)) => Some(JsonValueRef::Object(members)),
+                    Some(PartialRef::Array(arr)) => Some(JsonValueRef::Array(arr)),
+                    None => return Err(self.unexpected(event)),
+                },
+                Event::String(s) => Some(JsonValueRef::String(s)),
+                Event::Number(n) => Some(JsonValueRef::Number(self.number(n))),
+                Event::Bool(b) => Some(JsonValueRef::Boolean(b)),
+                Event::Null => Some(JsonValueRef::Null),
+            };
+
+            if let Some(value) = value {
+                match stack.last_mut() {
+                    None => return Ok(value),
+                    Some(PartialRef::Array(arr)) => arr.push(value),
+                    Some(PartialRef::Object(members, key)) => {
+                        members.push((key.take().unwrap_or_default(), value));
                     }
-                    Token::LBrace => {
-                        let objv2 = self.parse_object()?;
-                        value = Some(objv2);
+                }
+            }
+            event = self.next_event()?;
+        }
+    }
+
+    /// stores a number the way `NumberRepresentation` asks
+    fn number(&self, n: Cow<'a, str>) -> Cow<'a, str> {
+        match self.numbers {
+            NumberRepresentation::Exact => n,
+            NumberRepresentation::F64 => match float::parse_f64(&n) {
+                // integers below 2^53 are exact in an `f64`, keep them
+                // integers so `as_i64` still takes them
+                Some(f)
+                    if f.abs() < 9007199254740992.0
+                        && !(f == 0.0 && f.is_sign_negative())
+                        && n.bytes().all(|b| b.is_ascii_digit() || b == b'-') =>
+                {
+                    n
+                }
+                Some(f) if f.is_finite() => Cow::Owned(float::format_f64(f)),
+                _ => n,
+            },
+        }
+    }
+
+    /// checks that a streamed container starts with `first` and remembers
+    /// its depth
+    fn open_stream(&mut self, first: Event<'a>, open: Event<'a>) -> Result<(), ParseError> {
+        if first != open {
+            return Err(self.unexpected(first));
+        }
+        self.stream_depth = Some(self.events.depth());
+        Ok(())
+    }
+
+    /// one step of a streaming iterator over the container that starts with
+    /// `open`: hands the first event of the next item to `item`. Any error
+    /// ends the stream.
+    fn stream_next<T>(
+        &mut self,
+        open: Event<'a>,
+        item: impl FnOnce(&mut Self, Event<'a>) -> Result<T, ParseError>,
+    ) -> Option<Result<T, ParseError>> {
+        if self.finished {
+            return None;
+        }
+
+        match self.stream_step(open, item) {
+            Ok(Some(item)) => Some(Ok(item)),
+            Ok(None) => {
+                self.finished = true;
+                None
+            }
+            Err(e) => {
+                self.finished = true;
+                Some(Err(e))
+            }
+        }
+    }
+
+    fn stream_step<T>(
+        &mut self,
+        open: Event<'a>,
+        item: impl FnOnce(&mut Self, Event<'a>) -> Result<T, ParseError>,
+    ) -> Result<Option<T>, ParseError> {
+        let depth = match self.stream_depth {
+            Some(depth) => depth,
+            None => {
+                if self.events.is_at_end() {
+                    return Ok(None);
+                }
+                let first = self.next_event()?;
+                self.open_stream(first, open)?;
+                self.events.depth()
+            }
+        };
+
+        let event = self.next_event()?;
+        if matches!(event, Event::EndArray | Event::EndObject) && self.events.depth() < depth {
+            // the rest of the document is checked, but not built
+            while self.events.next_event()?.is_some() {}
+            self.events.expect_end()?;
+            return Ok(None);
+        }
+        item(self, event).map(Some)
+    }
+
+    /// reads the document up to the value at `pointer` and returns the
+    /// first event of that value
+    fn seek(&mut self, pointer: &str) -> Result<Event<'a>, ParseError> {
+        let Some(tokens) = split_pointer(pointer) else {
+            let kind = ParseErrorKind::InvalidPointer(pointer.to_string());
+            return Err(self.events.error(kind));
+        };
+        let not_found = |p: &Self| {
+            let kind = ParseErrorKind::PointerNotFound(pointer.to_string());
+            p.events.error(kind)
+        };
+
+        let mut event = self.next_event()?;
+        for token in tokens {
+            match event {
+                Event::StartObject => loop {
+                    match self.next_event()? {
+                        Event::Key(key) if key == token => break,
+                        Event::Key(_) => self.events.skip_value()?,
+                        _ => return Err(not_found(self)),
                     }
-                    Token::RBrace => {
-                        if key.is_empty() || value.is_none() {
-                            return Err("empty key or value in RBrace statement".into());
+                },
+                Event::StartArray => {
+                    let Some(index) = parse_pointer_index(&token) else {
+                        return Err(not_found(self));
+                    };
+                    for _ in 0..index {
+                        let element = self.next_event()?;
+                        if element == Event::EndArray {
+                            return Err(not_found(self));
                         }
-                        let _ = obj.insert(key, value.unwrap());
-                        break;
-                    }
-                    Token::LSquare => {
-                        let arr = self.parse_array()?;
-                        value = Some(arr)
-                    }
-                    Token::RSquare => {
-                        return Err("Unexpected closing square bracket in object".into())
-                    }
-                    Token::Illegal(c) => return Err(format!("Ilegal character, {c}")),
-                    Token::InvalidKeyword(key) => return Err(format!("invalid keyword {key}")),
-                    Token::EOF => {
-                        return Err("Unexpected end of input while parsing object".into());
+                        self.events.skip_rest(&element)?;
                     }
                 }
+                _ => return Err(not_found(self)),
             }
-        }
 
-        Ok(JsonValue::Object(obj))
+            event = self.next_event()?;
+            if event == Event::EndArray {
+                return Err(not_found(self));
+            }
+        }
+        Ok(event)
     }
 }
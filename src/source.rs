@@ -0,0 +1,109 @@
+use std::io::{self, Read};
+
+/// size of the buffer used to read from an `io::Read`
+const BUFFER_SIZE: usize = 8 * 1024;
+
+/// Where the lexer gets its bytes from.
+pub(crate) enum Source<'a> {
+    /// input already in memory
+    Slice {
+        bytes: &'a [u8],
+
+        /// the same input, when it was given as a `&str`
+        text: Option<&'a str>,
+
+        pos: usize,
+    },
+    /// input read through a fixed-size buffer
+    Reader(ReadSource<'a>),
+}
+
+impl<'a> Source<'a> {
+    pub(crate) fn from_str(input: &'a str) -> Self {
+        Self::Slice {
+            bytes: input.as_bytes(),
+            text: Some(input),
+            pos: 0,
+        }
+    }
+
+    /// the bytes are not checked up front: the lexer decodes every
+    /// character it reads, and reports invalid UTF-8 where it finds it
+    pub(crate) fn from_slice(input: &'a [u8]) -> Self {
+        Self::Slice {
+            bytes: input,
+            text: None,
+            pos: 0,
+        }
+    }
+
+    pub(crate) fn from_reader(reader: impl Read + 'a) -> Self {
+        Self::Reader(ReadSource {
+            reader: Box::new(reader),
+            buf: vec![0; BUFFER_SIZE],
+            pos: 0,
+            len: 0,
+        })
+    }
+
+    /// the next byte, `Ok(None)` at the end of the input
+    pub(crate) fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        match self {
+            Self::Slice { bytes, pos, .. } => {
+                let b = bytes.get(*pos).copied();
+                *pos += 1;
+                Ok(b)
+            }
+            Self::Reader(reader) => reader.next_byte(),
+        }
+    }
+
+    /// whether `borrow` can succeed, i.e. the input is in memory
+    pub(crate) fn can_borrow(&self) -> bool {
+        matches!(self, Self::Slice { .. })
+    }
+
+    /// the input text between two byte offsets, if it is in memory. Bytes
+    /// are only checked for the range asked for, which is just a token.
+    pub(crate) fn borrow(&self, start: usize, end: usize) -> Option<&'a str> {
+        match self {
+            Self::Slice {
+                text: Some(text), ..
+            } => text.get(start..end),
+            Self::Slice { bytes, .. } => std::str::from_utf8(bytes.get(start..end)?).ok(),
+            Self::Reader(_) => None,
+        }
+    }
+}
+
+/// Reads from an `io::Read`, keeping at most `BUFFER_SIZE` bytes of it in
+/// memory.
+pub(crate) struct ReadSource<'a> {
+    reader: Box<dyn Read + 'a>,
+    buf: Vec<u8>,
+
+    /// next byte to hand out
+    pos: usize,
+
+    /// number of bytes of `buf` filled by the last read
+    len: usize,
+}
+
+impl ReadSource<'_> {
+    fn next_byte(&mut self) -> io::Result<Option<u8>> {
+        while self.pos == self.len {
+            match self.reader.read(&mut self.buf) {
+                Ok(0) => return Ok(None),
+                Ok(n) => {
+                    self.pos = 0;
+                    self.len = n;
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+        let b = self.buf[self.pos];
+        self.pos += 1;
+        Ok(Some(b))
+    }
+}
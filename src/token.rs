@@ -1,7 +1,51 @@
-use std::fmt::Display;
+use std::{borrow::Cow, fmt::Display};
 
+/// Location of a token in the source text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    /// byte offset of the first byte of the token
+    pub start: usize,
+
+    /// byte offset just past the last byte of the token
+    pub end: usize,
+
+    /// line of the first character, starting at 1
+    pub line: usize,
+
+    /// column of the first character, starting at 1
+    pub column: usize,
+}
+
+/// The empty span at the start of the input, on line 1, column 1.
+impl Default for Span {
+    fn default() -> Self {
+        Self {
+            start: 0,
+            end: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}, column {}", self.line, self.column)
+    }
+}
+
+/// A token together with the place where it was found.
 #[derive(Debug, PartialEq)]
-pub enum Token {
+pub struct SpannedToken<'a> {
+    pub token: Token<'a>,
+    pub span: Span,
+}
+
+/// A token of JSON text. Strings and numbers borrow from the input when they
+/// can, and only allocate when escapes had to be decoded or the input comes
+/// from a reader.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token<'a> {
     //  structural characters
     LBrace,  // {
     RBrace,  // }
@@ -11,17 +55,29 @@ pub enum Token {
     Colon,   // :
 
     // Literals
-    StringLiteral(String), // strings
-    NumberLiteral(String), // 1, 21.5, 2e10
-    BooleanLiteral(bool),  // true, false
-    NullLiteral,           // null
+    StringLiteral(Cow<'a, str>), // strings
+    NumberLiteral(Cow<'a, str>), // 1, 21.5, 2e10
+    BooleanLiteral(bool),        // true, false
+    NullLiteral,                 // null
 
     EOF,
     Illegal(char),
     InvalidKeyword(String),
+    InvalidNumber(String), // 01, 1., -.5, 1e+
+    InvalidEscape(String), // \q, \u12G4, lone surrogates
+    UnterminatedString,
+    UnterminatedComment,
+    ControlCharacterInString(char), // raw U+0000 to U+001F
+    InvalidUtf8,                    // bytes that are not valid UTF-8
+    IoError(String),                // a reader failed
+
+    // `Limits` crossed, with the limit
+    InputTooLarge(usize),
+    StringTooLong(usize),
+    NumberTooLong(usize),
 }
 
-impl Display for Token {
+impl Display for Token<'_> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::LBrace => write!(f, "{{"),
@@ -36,6 +92,16 @@ impl Display for Token {
             Self::NullLiteral => write!(f, "null"),
             Self::Illegal(c) => write!(f, "{c}"),
             Self::InvalidKeyword(key) => write!(f, "{key}"),
+            Self::InvalidNumber(n) => write!(f, "{n}"),
+            Self::InvalidEscape(escape) => write!(f, "{escape}"),
+            Self::UnterminatedString => write!(f, "\""),
+            Self::UnterminatedComment => write!(f, "/*"),
+            Self::ControlCharacterInString(c) => write!(f, "{}", c.escape_unicode()),
+            Self::InvalidUtf8
+            | Self::IoError(_)
+            | Self::InputTooLarge(_)
+            | Self::StringTooLong(_)
+            | Self::NumberTooLong(_) => write!(f, ""),
             Self::EOF => write!(f, ""),
         }
     }
@@ -0,0 +1,55 @@
+/// What the parser should do after a `JsonVisitor` callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VisitControl {
+    /// keep going
+    Continue,
+    /// skip the value that was just announced: the whole object or array
+    /// after `on_object_start`/`on_array_start`, or the member's value after
+    /// `on_key`. Skipped input is still checked. Has no effect on scalars.
+    Skip,
+    /// stop parsing right away
+    Abort,
+}
+
+/// Push-style consumer of a JSON document, driven by `Parser::visit`.
+///
+/// Every callback does nothing by default, so a visitor only implements
+/// the ones it cares about.
+pub trait JsonVisitor {
+    fn on_object_start(&mut self) -> VisitControl {
+        VisitControl::Continue
+    }
+
+    fn on_object_end(&mut self) -> VisitControl {
+        VisitControl::Continue
+    }
+
+    fn on_array_start(&mut self) -> VisitControl {
+        VisitControl::Continue
+    }
+
+    fn on_array_end(&mut self) -> VisitControl {
+        VisitControl::Continue
+    }
+
+    fn on_key(&mut self, _key: &str) -> VisitControl {
+        VisitControl::Continue
+    }
+
+    fn on_string(&mut self, _value: &str) -> VisitControl {
+        VisitControl::Continue
+    }
+
+    /// `value` is the number as written in the input
+    fn on_number(&mut self, _value: &str) -> VisitControl {
+        VisitControl::Continue
+    }
+
+    fn on_bool(&mut self, _value: bool) -> VisitControl {
+        VisitControl::Continue
+    }
+
+    fn on_null(&mut self) -> VisitControl {
+        VisitControl::Continue
+    }
+}
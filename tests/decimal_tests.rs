@@ -0,0 +1,177 @@
+#![cfg(feature = "arbitrary_precision")]
+
+use rijson::{
+    decimal::Decimal,
+    number::{JsonNumber, NumberError},
+    parser::{JsonValue, Parser},
+};
+
+fn decimal(text: &str) -> Decimal {
+    text.parse().unwrap()
+}
+
+fn number(text: &str) -> JsonNumber {
+    text.parse().unwrap()
+}
+
+#[test]
+fn test_canonical_formatting() {
+    let cases = [
+        ("0", "0"),
+        ("-0.000", "0"),
+        ("1.50", "1.5"),
+        ("15e-1", "1.5"),
+        ("1.0e+10", "10000000000"),
+        ("-0.00012", "-0.00012"),
+        ("1e-7", "1e-7"),
+        ("1e20", "100000000000000000000"),
+        ("1e21", "1e+21"),
+        ("-1.25E30", "-1.25e+30"),
+        ("12345678901234567890123", "12345678901234567890123"),
+        (
+            "12345678901234567890.000000000001",
+            "12345678901234567890.000000000001",
+        ),
+        ("1e-9223372036854775807", "1e-9223372036854775807"),
+    ];
+    for (input, expected) in cases {
+        assert_eq!(
+            decimal(input).to_string(),
+            expected,
+            "Failed for input: {}",
+            input
+        );
+        assert_eq!(
+            decimal(expected),
+            decimal(input),
+            "Failed for input: {}",
+            input
+        );
+    }
+
+    assert_eq!(
+        "1e9223372036854775808".parse::<Decimal>(),
+        Err(NumberError::OutOfRange)
+    );
+    assert_eq!("1.".parse::<Decimal>(), Err(NumberError::Invalid));
+    assert_eq!("NaN".parse::<Decimal>(), Err(NumberError::Invalid));
+}
+
+#[test]
+fn test_arithmetic() {
+    let cases = [
+        ("0.1", "0.2", "0.3", "-0.1", "0.02"),
+        (
+            "12345678901234567890.000000000001",
+            "1",
+            "12345678901234567891.000000000001",
+            "12345678901234567889.000000000001",
+            "12345678901234567890.000000000001",
+        ),
+        ("-1.5", "1.5", "0", "-3", "-2.25"),
+        ("99.99", "0.01", "100", "99.98", "0.9999"),
+        (
+            "1e30",
+            "-1e-30",
+            "999999999999999999999999999999.999999999999999999999999999999",
+            "1000000000000000000000000000000.000000000000000000000000000001",
+            "-1",
+        ),
+    ];
+    for (a, b, sum, difference, product) in cases {
+        let (a, b) = (decimal(a), decimal(b));
+        assert_eq!(
+            a.checked_add(&b),
+            Some(decimal(sum)),
+            "Failed for input: {} + {}",
+            a,
+            b
+        );
+        assert_eq!(
+            a.checked_sub(&b),
+            Some(decimal(difference)),
+            "Failed for input: {} - {}",
+            a,
+            b
+        );
+        assert_eq!(
+            a.checked_mul(&b),
+            Some(decimal(product)),
+            "Failed for input: {} * {}",
+            a,
+            b
+        );
+    }
+
+    let tiny = decimal("1e-9223372036854775807");
+    assert_eq!(tiny.checked_mul(&tiny), None);
+
+    // far apart exponents fail instead of padding with zeros
+    let huge = decimal("1e9000000000000000000");
+    assert_eq!(huge.checked_add(&decimal("1")), None);
+    assert_eq!(decimal("1").checked_sub(&huge), None);
+    assert_eq!(
+        decimal("1e1000").checked_add(&decimal("1")),
+        Some(decimal(&format!("1{}1", "0".repeat(999))))
+    );
+}
+
+#[test]
+fn test_ordering() {
+    let mut values: Vec<Decimal> = ["10", "-2.5", "0", "1e-3", "-10", "9.99", "1e2"]
+        .into_iter()
+        .map(decimal)
+        .collect();
+    values.sort();
+    let sorted: Vec<String> = values.iter().map(Decimal::to_string).collect();
+    assert_eq!(sorted, ["-10", "-2.5", "0", "0.001", "9.99", "10", "100"]);
+}
+
+#[test]
+fn test_conversions() {
+    assert_eq!(decimal("1.5e1").to_i64(), Ok(15));
+    assert_eq!(decimal("1e2").to_u64(), Ok(100));
+    assert_eq!(decimal("2.5").to_i64(), Err(NumberError::NotAnInteger));
+    assert_eq!(decimal("-1").to_u64(), Err(NumberError::OutOfRange));
+    assert_eq!(decimal("1e40").to_i128(), Err(NumberError::OutOfRange));
+    assert_eq!(
+        decimal("-170141183460469231731687303715884105728").to_i128(),
+        Ok(i128::MIN)
+    );
+    assert_eq!(decimal("0.1").to_f64(), Ok(0.1));
+    assert_eq!(decimal("1e400").to_f64(), Err(NumberError::OutOfRange));
+    assert_eq!(Decimal::from(-42i8), decimal("-42"));
+    assert_eq!(Decimal::from(u128::MAX).to_string(), u128::MAX.to_string());
+}
+
+#[test]
+fn test_parsed_numbers_are_exact() {
+    let input = r#"{"amount": 12345678901234567890.000000000001, "count": 3e0, "fee": 0.10}"#;
+    let value = Parser::new(input).parse().unwrap();
+    assert_eq!(
+        value.to_string(),
+        r#"{"amount": 12345678901234567890.000000000001, "count": 3, "fee": 0.1}"#
+    );
+
+    let JsonValue::Object(map) = value else {
+        panic!("expected an object");
+    };
+    let Some(JsonValue::Number(amount)) = map.get("amount") else {
+        panic!("expected a number");
+    };
+    let Some(JsonValue::Number(count)) = map.get("count") else {
+        panic!("expected a number");
+    };
+    let total = amount
+        .as_decimal()
+        .unwrap()
+        .checked_mul(&count.as_decimal().unwrap())
+        .unwrap();
+    assert_eq!(total.to_string(), "37037036703703703670.000000000003");
+    assert_eq!(count.as_u64(), Ok(3));
+    assert!(count.is_integer());
+
+    assert_eq!(number("1.0"), number("1"));
+    assert_eq!(JsonNumber::from(decimal("2.50")).as_str(), "2.5");
+    assert_eq!(number("NaN").as_decimal(), Err(NumberError::Invalid));
+}
@@ -0,0 +1,152 @@
+use rijson::{
+    error::ParseErrorKind,
+    event::{Event, EventReader},
+    lexer::LoneSurrogatePolicy,
+};
+
+fn events(input: &str) -> Vec<Event<'_>> {
+    EventReader::new(input).map(|e| e.unwrap()).collect()
+}
+
+#[test]
+fn test_events() {
+    let input =
+        r#"{"name": "rijson", "tags": ["json", 1, true], "meta": {}, "none": null, "list": []}"#;
+    let expected = vec![
+        Event::StartObject,
+        Event::Key("name".into()),
+        Event::String("rijson".into()),
+        Event::Key("tags".into()),
+        Event::StartArray,
+        Event::String("json".into()),
+        Event::Number("1".into()),
+        Event::Bool(true),
+        Event::EndArray,
+        Event::Key("meta".into()),
+        Event::StartObject,
+        Event::EndObject,
+        Event::Key("none".into()),
+        Event::Null,
+        Event::Key("list".into()),
+        Event::StartArray,
+        Event::EndArray,
+        Event::EndObject,
+    ];
+    assert_eq!(events(input), expected);
+
+    assert_eq!(events("false"), vec![Event::Bool(false)]);
+    assert_eq!(
+        events("[[]]"),
+        vec![
+            Event::StartArray,
+            Event::StartArray,
+            Event::EndArray,
+            Event::EndArray
+        ]
+    );
+}
+
+#[test]
+fn test_event_spans_depth_and_path() {
+    let input = "{\"a\": [10, {\"b\": null}]}";
+    let mut r = EventReader::new(input);
+    let mut seen = vec![];
+    while let Some(event) = r.next_event().unwrap() {
+        seen.push((event, r.span().start, r.depth(), r.path().to_string()));
+    }
+
+    let expected = [
+        (Event::StartObject, 0, 1, ""),
+        (Event::Key("a".into()), 1, 1, "/a"),
+        (Event::StartArray, 6, 2, "/a"),
+        (Event::Number("10".into()), 7, 2, "/a"),
+        (Event::StartObject, 11, 3, "/a/1"),
+        (Event::Key("b".into()), 12, 3, "/a/1/b"),
+        (Event::Null, 17, 3, "/a/1"),
+        (Event::EndObject, 21, 2, "/a"),
+        (Event::EndArray, 22, 1, ""),
+        (Event::EndObject, 23, 0, ""),
+    ];
+    assert_eq!(seen.len(), expected.len());
+    for (got, (event, start, depth, path)) in seen.into_iter().zip(expected) {
+        assert_eq!(got, (event, start, depth, path.to_string()));
+    }
+}
+
+#[test]
+fn test_event_grammar_errors() {
+    let cases = [
+        ("[1,]", 2, ParseErrorKind::TrailingComma),
+        ("{\"a\" 1}", 1, ParseErrorKind::MissingColon),
+        ("[1 2]", 2, ParseErrorKind::MissingComma),
+        (
+            "{\"a\": }",
+            2,
+            ParseErrorKind::UnexpectedToken("}".to_string()),
+        ),
+        ("[", 1, ParseErrorKind::UnexpectedEof),
+    ];
+
+    for (input, valid_events, kind) in cases {
+        let mut r = EventReader::new(input);
+        for _ in 0..valid_events {
+            assert!(
+                r.next_event().unwrap().is_some(),
+                "Failed for input: {}",
+                input
+            );
+        }
+        let err = r.next_event().err().unwrap();
+        assert_eq!(err.kind, kind, "Failed for input: {}", input);
+        assert_eq!(r.next_event().unwrap(), None);
+    }
+}
+
+#[test]
+fn test_skip_value() {
+    let input = r#"{"skip": {"deep": [1, [2, {"x": 3}]]}, "keep": 4}"#;
+    let mut r = EventReader::new(input);
+    assert_eq!(r.next_event().unwrap(), Some(Event::StartObject));
+    assert_eq!(r.next_event().unwrap(), Some(Event::Key("skip".into())));
+    r.skip_value().unwrap();
+    assert_eq!(r.next_event().unwrap(), Some(Event::Key("keep".into())));
+    assert_eq!(r.next_event().unwrap(), Some(Event::Number("4".into())));
+    assert_eq!(r.next_event().unwrap(), Some(Event::EndObject));
+    assert_eq!(r.next_event().unwrap(), None);
+    assert!(r.expect_end().is_ok());
+
+    let mut r = EventReader::new(r#"[{"a": [1 2]}]"#);
+    r.next_event().unwrap();
+    assert_eq!(
+        r.skip_value().err().unwrap().kind,
+        ParseErrorKind::MissingComma
+    );
+}
+
+#[test]
+fn test_preserved_surrogates() {
+    let input = r#"{"k\udc00": ["v\ud800", "plain"]}"#;
+    let mut r = EventReader::new(input).with_lone_surrogate_policy(LoneSurrogatePolicy::Preserve);
+    let mut wtf8 = vec![];
+    while let Some(event) = r.next_event().unwrap() {
+        wtf8.push((event, r.wtf8().map(<[u8]>::to_vec)));
+    }
+    assert_eq!(
+        wtf8,
+        [
+            (Event::StartObject, None),
+            (
+                Event::Key("k\u{FFFD}".into()),
+                Some(b"k\xed\xb0\x80".to_vec())
+            ),
+            (Event::StartArray, None),
+            (
+                Event::String("v\u{FFFD}".into()),
+                Some(b"v\xed\xa0\x80".to_vec())
+            ),
+            (Event::String("plain".into()), None),
+            (Event::EndArray, None),
+            (Event::EndObject, None),
+        ]
+    );
+}
@@ -0,0 +1,134 @@
+use rijson::{
+    float::{format_f64, parse_f64},
+    number::JsonNumber,
+    options::{NumberRepresentation, ParserOptions},
+    parser::Parser,
+};
+
+/// xorshift, so the random cases are the same on every run
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[test]
+fn test_parse_hard_cases() {
+    let cases = [
+        "0",
+        "-0.0",
+        "1e-10",
+        "1.0e+10",
+        "0.1",
+        "0.30000000000000004",
+        "9007199254740993",
+        "9007199254740992.000000000000000000000000001",
+        "2.2250738585072011e-308",
+        "2.2250738585072012e-308",
+        "2.2250738585072014e-308",
+        "4.9406564584124654e-324",
+        "2.4703282292062327e-324",
+        "2.4703282292062328e-324",
+        "1e-400",
+        "1.7976931348623157e308",
+        "1.7976931348623158e308",
+        "1.7976931348623159e308",
+        "1e400",
+        "7.038531e-26",
+        "8.988465674311579e+307",
+        "1448997445238699",
+        "123456789012345678901234567890e-30",
+        "2.47032822920623272088284396434110686182529901307162382212792841250337753635104375932649918180817996189898282347722858865463328355177969898199387398005390939063150356595155702263922908583924491051844359318028499365361525003193704576782492193656236698636584807570015857692699037063119282795585513329278343384093519780155312465972635795746227664652728272200563740064854999770965994704540208281662262378573934507363390079677619305775067401763246736009689513405355374585166611342237666786041621596804619144672918403005300575308490487653917113865916462395249126236538818796362393732804238910186723484976682350898633885879256283027559956575244555072551893136908362547791869486679949683240497058210285131854513962138377228261454376934125320985913276672363281255e-324",
+    ];
+    for input in cases {
+        assert_eq!(
+            parse_f64(input).map(f64::to_bits),
+            input.parse::<f64>().ok().map(f64::to_bits),
+            "Failed for input: {}",
+            input
+        );
+    }
+
+    for input in ["", "01", "1.", ".5", "+1", "1e", "NaN", "Infinity"] {
+        assert_eq!(parse_f64(input), None, "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_parse_random() {
+    let mut rng = Rng(0x9E37_79B9_7F4A_7C15);
+    for _ in 0..20_000 {
+        let digits = 1 + rng.next() % 25;
+        let mantissa: String = (0..digits)
+            .map(|_| char::from(b'0' + (rng.next() % 10) as u8))
+            .collect();
+        let mantissa = mantissa.trim_start_matches('0');
+        let mantissa = if mantissa.is_empty() { "0" } else { mantissa };
+        let exponent = (rng.next() % 700) as i64 - 350;
+        let input = format!("{mantissa}e{exponent}");
+        assert_eq!(
+            parse_f64(&input).map(f64::to_bits),
+            input.parse::<f64>().ok().map(f64::to_bits),
+            "Failed for input: {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_format_shortest() {
+    let cases = [
+        (0.0, "0.0"),
+        (-0.0, "-0.0"),
+        (1e-10, "1e-10"),
+        (1e10, "10000000000.0"),
+        (0.1, "0.1"),
+        (0.1 + 0.2, "0.30000000000000004"),
+        (1.1, "1.1"),
+        (-2.5e-5, "-2.5e-5"),
+        (1e16, "1e16"),
+        (123456.789, "123456.789"),
+        (f64::MAX, "1.7976931348623157e308"),
+        (f64::MIN_POSITIVE, "2.2250738585072014e-308"),
+        (5e-324, "5e-324"),
+        (f64::NAN, "NaN"),
+        (f64::NEG_INFINITY, "-Infinity"),
+    ];
+    for (f, expected) in cases {
+        assert_eq!(format_f64(f), expected, "Failed for input: {}", expected);
+    }
+}
+
+#[test]
+fn test_format_round_trips() {
+    let mut rng = Rng(0x2545_F491_4F6C_DD1D);
+    for _ in 0..20_000 {
+        let f = f64::from_bits(rng.next());
+        if !f.is_finite() {
+            continue;
+        }
+        let text = format_f64(f);
+        assert_eq!(text, format!("{f:?}"), "Failed for input: {:e}", f);
+        assert_eq!(parse_f64(&text), Some(f), "Failed for input: {}", text);
+    }
+}
+
+#[test]
+fn test_numbers_reserialize_without_drift() {
+    let input = "[1e-10, 1.10, 1.0e+10, 0.1, 5e-324]";
+    let options = ParserOptions::new().with_number_representation(NumberRepresentation::F64);
+    let value = Parser::new(input).with_options(&options).parse().unwrap();
+    let text = value.to_string();
+    if cfg!(not(feature = "arbitrary_precision")) {
+        assert_eq!(text, "[1e-10, 1.1, 10000000000.0, 0.1, 5e-324]");
+    }
+
+    let again = Parser::new(&text).with_options(&options).parse().unwrap();
+    assert_eq!(again, value);
+    assert_eq!(JsonNumber::from_f64(1e-10).unwrap().as_f64(), Ok(1e-10));
+}
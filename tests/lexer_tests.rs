@@ -1,9 +1,14 @@
-use rijson::{lexer, token::Token};
+use std::borrow::Cow;
+
+use rijson::{
+    lexer::{self, LoneSurrogatePolicy},
+    token::{Span, Token},
+};
 
 #[test]
 fn test_simple_lexer() {
     let input = String::from(r#"[]{},:?"#);
-    let expected = vec![
+    let expected = [
         Token::LSquare,
         Token::RSquare,
         Token::LBrace,
@@ -12,10 +17,10 @@ fn test_simple_lexer() {
         Token::Colon,
         Token::Illegal('?'),
     ];
-    let mut l = lexer::Lexer::new(input.chars().collect::<Vec<char>>());
-    for i in 0..input.len() {
+    let mut l = lexer::Lexer::new(&input);
+    for expected_token in &expected {
         let tok = l.next_token();
-        assert_eq!(expected[i], tok);
+        assert_eq!(*expected_token, tok);
     }
     let eof = l.next_token();
     assert_eq!(eof, Token::EOF)
@@ -23,10 +28,10 @@ fn test_simple_lexer() {
 
 #[test]
 fn test_empty_string() {
-    let input: Vec<char> = vec!['"', '"'];
+    let input = "\"\"";
     let mut l = lexer::Lexer::new(input);
     let mut tok = l.next_token();
-    assert_eq!(Token::StringLiteral("".to_string()), tok);
+    assert_eq!(Token::StringLiteral("".into()), tok);
     tok = l.next_token();
     assert_eq!(Token::EOF, tok)
 }
@@ -52,56 +57,56 @@ fn test_string() {
     );
     let expected = vec![
         Token::LBrace,
-        Token::StringLiteral("name".to_string()),
+        Token::StringLiteral("name".into()),
         Token::Colon,
-        Token::StringLiteral("Michael".to_string()),
+        Token::StringLiteral("Michael".into()),
         Token::Comma,
-        Token::StringLiteral("array".to_string()),
+        Token::StringLiteral("array".into()),
         Token::Colon,
         Token::LSquare,
         Token::RSquare,
         Token::Comma,
-        Token::StringLiteral("country".to_string()),
+        Token::StringLiteral("country".into()),
         Token::Colon,
-        Token::StringLiteral("Colombia[]".to_string()),
+        Token::StringLiteral("Colombia[]".into()),
         Token::Comma,
-        Token::StringLiteral("address".to_string()),
+        Token::StringLiteral("address".into()),
         Token::Colon,
-        Token::StringLiteral("".to_string()),
+        Token::StringLiteral("".into()),
         Token::Comma,
-        Token::StringLiteral("city".to_string()),
+        Token::StringLiteral("city".into()),
         Token::Colon,
         Token::NullLiteral,
         Token::Comma,
-        Token::StringLiteral("x".to_string()),
+        Token::StringLiteral("x".into()),
         Token::Colon,
         Token::InvalidKeyword("nullable".to_string()),
         Token::Comma,
-        Token::StringLiteral("y".to_string()),
+        Token::StringLiteral("y".into()),
         Token::Colon,
         Token::InvalidKeyword("nul".to_string()),
         Token::Comma,
-        Token::StringLiteral("long".to_string()),
+        Token::StringLiteral("long".into()),
         Token::Colon,
         Token::BooleanLiteral(true),
         Token::Comma,
-        Token::StringLiteral("large".to_string()),
+        Token::StringLiteral("large".into()),
         Token::Colon,
         Token::BooleanLiteral(false),
         Token::Comma,
-        Token::StringLiteral("true".to_string()),
+        Token::StringLiteral("true".into()),
         Token::Colon,
         Token::InvalidKeyword("tru".to_string()),
         Token::Comma,
-        Token::StringLiteral("false".to_string()),
+        Token::StringLiteral("false".into()),
         Token::Colon,
-        Token::StringLiteral("falsee\"a\" gol".to_string()),
+        Token::StringLiteral("falsee\"a\" gol".into()),
         Token::RBrace,
     ];
-    let mut l = lexer::Lexer::new(input.chars().collect());
-    for i in 0..expected.len() {
+    let mut l = lexer::Lexer::new(&input);
+    for expected_token in &expected {
         let tok = l.next_token();
-        assert_eq!(expected[i], tok);
+        assert_eq!(*expected_token, tok);
     }
     let eof = l.next_token();
     assert_eq!(eof, Token::EOF)
@@ -135,78 +140,78 @@ fn test_structure_without_numbers() {
 
     let expected = vec![
         Token::LBrace,
-        Token::StringLiteral("active".to_string()),
+        Token::StringLiteral("active".into()),
         Token::Colon,
         Token::BooleanLiteral(true),
         Token::Comma,
-        Token::StringLiteral("details".to_string()),
+        Token::StringLiteral("details".into()),
         Token::Colon,
         Token::LBrace,
-        Token::StringLiteral("tags".to_string()),
+        Token::StringLiteral("tags".into()),
         Token::Colon,
         Token::LSquare,
-        Token::StringLiteral("json".to_string()),
+        Token::StringLiteral("json".into()),
         Token::Comma,
-        Token::StringLiteral("lexer".to_string()),
+        Token::StringLiteral("lexer".into()),
         Token::Comma,
-        Token::StringLiteral("test".to_string()),
+        Token::StringLiteral("test".into()),
         Token::Comma,
-        Token::StringLiteral("complex\"".to_string()),
+        Token::StringLiteral("complex\"".into()),
         Token::RSquare,
         Token::Comma,
-        Token::StringLiteral("data".to_string()),
+        Token::StringLiteral("data".into()),
         Token::Colon,
         Token::NullLiteral,
         Token::Comma,
-        Token::StringLiteral("description".to_string()),
+        Token::StringLiteral("description".into()),
         Token::Colon,
-        Token::StringLiteral("This is a string with \"quotes\" and\nnewlines.".to_string()),
+        Token::StringLiteral("This is a string with \"quotes\" and\nnewlines.".into()),
         Token::Comma,
-        Token::StringLiteral("path".to_string()),
+        Token::StringLiteral("path".into()),
         Token::Colon,
-        Token::StringLiteral("C:\\Users\\Test".to_string()),
+        Token::StringLiteral("C:\\Users\\Test".into()),
         Token::RBrace,
         Token::Comma,
-        Token::StringLiteral("list".to_string()),
+        Token::StringLiteral("list".into()),
         Token::Colon,
         Token::LSquare,
         Token::BooleanLiteral(false),
         Token::Comma,
-        Token::StringLiteral("item three".to_string()),
+        Token::StringLiteral("item three".into()),
         Token::Comma,
         Token::LBrace,
-        Token::StringLiteral("nested_key".to_string()),
+        Token::StringLiteral("nested_key".into()),
         Token::Colon,
-        Token::StringLiteral("nested_value".to_string()),
+        Token::StringLiteral("nested_value".into()),
         Token::RBrace,
         Token::RSquare,
         Token::Comma,
-        Token::StringLiteral("unclosed_string".to_string()),
+        Token::StringLiteral("unclosed_string".into()),
         Token::Colon,
-        Token::StringLiteral("hello, world".to_string()),
+        Token::StringLiteral("hello, world".into()),
         Token::Comma,
-        Token::StringLiteral("unknown_symbol".to_string()),
+        Token::StringLiteral("unknown_symbol".into()),
         Token::Colon,
         Token::Illegal('$'),
         Token::Comma,
-        Token::StringLiteral("invalid_true".to_string()),
+        Token::StringLiteral("invalid_true".into()),
         Token::Colon,
         Token::InvalidKeyword("truee".to_string()),
         Token::Comma,
-        Token::StringLiteral("invalid_null".to_string()),
+        Token::StringLiteral("invalid_null".into()),
         Token::Colon,
         Token::InvalidKeyword("nulll".to_string()),
         Token::Comma,
-        Token::StringLiteral("just_an_identifier".to_string()),
+        Token::StringLiteral("just_an_identifier".into()),
         Token::Colon,
         Token::InvalidKeyword("my_variable_name".to_string()),
         Token::RBrace,
     ];
 
-    let mut l = lexer::Lexer::new(input.chars().collect());
-    for i in 0..expected.len() {
+    let mut l = lexer::Lexer::new(&input);
+    for expected_token in &expected {
         let tok = l.next_token();
-        assert_eq!(expected[i], tok);
+        assert_eq!(*expected_token, tok);
     }
     let eof = l.next_token();
     assert_eq!(eof, Token::EOF);
@@ -226,23 +231,23 @@ fn test_number() {
 
     let expected = vec![
         Token::LBrace,
-        Token::StringLiteral("age".to_string()),
+        Token::StringLiteral("age".into()),
         Token::Colon,
-        Token::NumberLiteral("1".to_string()),
+        Token::NumberLiteral("1".into()),
         Token::Comma,
-        Token::StringLiteral("fail".to_string()),
+        Token::StringLiteral("fail".into()),
         Token::Colon,
-        Token::NumberLiteral("1.212345678".to_string()),
+        Token::NumberLiteral("1.212345678".into()),
         Token::Comma,
-        Token::StringLiteral("number".to_string()),
+        Token::StringLiteral("number".into()),
         Token::Colon,
-        Token::NumberLiteral("-2e-10".to_string()),
+        Token::NumberLiteral("-2e-10".into()),
         Token::RBrace,
     ];
-    let mut l = lexer::Lexer::new(input.chars().collect());
-    for i in 0..expected.len() {
+    let mut l = lexer::Lexer::new(&input);
+    for expected_token in &expected {
         let tok = l.next_token();
-        assert_eq!(expected[i], tok);
+        assert_eq!(*expected_token, tok);
     }
     let eof = l.next_token();
     assert_eq!(eof, Token::EOF);
@@ -273,73 +278,73 @@ fn test_number_comprehensive() {
     let expected = vec![
         Token::LBrace,
         // "integer": 42
-        Token::StringLiteral("integer".to_string()),
+        Token::StringLiteral("integer".into()),
         Token::Colon,
-        Token::NumberLiteral("42".to_string()),
+        Token::NumberLiteral("42".into()),
         Token::Comma,
         // "negative": -17
-        Token::StringLiteral("negative".to_string()),
+        Token::StringLiteral("negative".into()),
         Token::Colon,
-        Token::NumberLiteral("-17".to_string()),
+        Token::NumberLiteral("-17".into()),
         Token::Comma,
         // "zero": 0
-        Token::StringLiteral("zero".to_string()),
+        Token::StringLiteral("zero".into()),
         Token::Colon,
-        Token::NumberLiteral("0".to_string()),
+        Token::NumberLiteral("0".into()),
         Token::Comma,
         // "decimal": 3.14159
-        Token::StringLiteral("decimal".to_string()),
+        Token::StringLiteral("decimal".into()),
         Token::Colon,
-        Token::NumberLiteral("3.14159".to_string()),
+        Token::NumberLiteral("3.14159".into()),
         Token::Comma,
         // "negative_decimal": -2.718
-        Token::StringLiteral("negative_decimal".to_string()),
+        Token::StringLiteral("negative_decimal".into()),
         Token::Colon,
-        Token::NumberLiteral("-2.718".to_string()),
+        Token::NumberLiteral("-2.718".into()),
         Token::Comma,
         // "scientific_positive": 1.23e4
-        Token::StringLiteral("scientific_positive".to_string()),
+        Token::StringLiteral("scientific_positive".into()),
         Token::Colon,
-        Token::NumberLiteral("1.23e4".to_string()),
+        Token::NumberLiteral("1.23e4".into()),
         Token::Comma,
         // "scientific_negative": 2.5e-3
-        Token::StringLiteral("scientific_negative".to_string()),
+        Token::StringLiteral("scientific_negative".into()),
         Token::Colon,
-        Token::NumberLiteral("2.5e-3".to_string()),
+        Token::NumberLiteral("2.5e-3".into()),
         Token::Comma,
         // "scientific_uppercase": 1E6
-        Token::StringLiteral("scientific_uppercase".to_string()),
+        Token::StringLiteral("scientific_uppercase".into()),
         Token::Colon,
-        Token::NumberLiteral("1E6".to_string()),
+        Token::NumberLiteral("1E6".into()),
         Token::Comma,
         // "scientific_with_plus": 6.02e+23
-        Token::StringLiteral("scientific_with_plus".to_string()),
+        Token::StringLiteral("scientific_with_plus".into()),
         Token::Colon,
-        Token::NumberLiteral("6.02e+23".to_string()),
+        Token::NumberLiteral("6.02e+23".into()),
         Token::Comma,
         // "very_small": 1e-100
-        Token::StringLiteral("very_small".to_string()),
+        Token::StringLiteral("very_small".into()),
         Token::Colon,
-        Token::NumberLiteral("1e-100".to_string()),
+        Token::NumberLiteral("1e-100".into()),
         Token::Comma,
         // "very_large": 9.999e99
-        Token::StringLiteral("very_large".to_string()),
+        Token::StringLiteral("very_large".into()),
         Token::Colon,
-        Token::NumberLiteral("9.999e99".to_string()),
+        Token::NumberLiteral("9.999e99".into()),
         Token::Comma,
         // "zero_decimal": 0.0
-        Token::StringLiteral("zero_decimal".to_string()),
+        Token::StringLiteral("zero_decimal".into()),
         Token::Colon,
-        Token::NumberLiteral("0.0".to_string()),
+        Token::NumberLiteral("0.0".into()),
         Token::Comma,
         // "leading_zero_decimal": 0.123
-        Token::StringLiteral("leading_zero_decimal".to_string()),
+        Token::StringLiteral("leading_zero_decimal".into()),
         Token::Colon,
-        Token::NumberLiteral("0.123".to_string()),
+        Token::NumberLiteral("0.123".into()),
         Token::RBrace,
     ];
 
-    let mut l = lexer::Lexer::new(input.chars().collect());
+    let mut l = lexer::Lexer::new(&input);
     for (i, expected_token) in expected.iter().enumerate() {
         let tok = l.next_token();
         assert_eq!(*expected_token, tok, "Token mismatch at position {}", i);
@@ -370,11 +375,11 @@ fn test_number_edge_cases() {
     ];
 
     for (input, expected) in test_cases {
-        let mut lexer = lexer::Lexer::new(input.chars().collect());
+        let mut lexer = lexer::Lexer::new(input);
         let token = lexer.next_token();
         assert_eq!(
             token,
-            Token::NumberLiteral(expected.to_string()),
+            Token::NumberLiteral(expected.into()),
             "Failed for input: {}",
             input
         );
@@ -390,19 +395,19 @@ fn test_numbers_in_array() {
     let input = "[1, -2, 3.14, 1e10, -2.5e-3]";
     let expected = vec![
         Token::LSquare,
-        Token::NumberLiteral("1".to_string()),
+        Token::NumberLiteral("1".into()),
         Token::Comma,
-        Token::NumberLiteral("-2".to_string()),
+        Token::NumberLiteral("-2".into()),
         Token::Comma,
-        Token::NumberLiteral("3.14".to_string()),
+        Token::NumberLiteral("3.14".into()),
         Token::Comma,
-        Token::NumberLiteral("1e10".to_string()),
+        Token::NumberLiteral("1e10".into()),
         Token::Comma,
-        Token::NumberLiteral("-2.5e-3".to_string()),
+        Token::NumberLiteral("-2.5e-3".into()),
         Token::RSquare,
     ];
 
-    let mut lexer = lexer::Lexer::new(input.chars().collect());
+    let mut lexer = lexer::Lexer::new(input);
     for (i, expected_token) in expected.iter().enumerate() {
         let tok = lexer.next_token();
         assert_eq!(*expected_token, tok, "Token mismatch at position {}", i);
@@ -412,25 +417,289 @@ fn test_numbers_in_array() {
 #[test]
 fn test_number_boundaries() {
     let input = r#"{"count":42,"name":"test","active":true}"#;
-    let mut lexer = lexer::Lexer::new(input.chars().collect());
+    let mut lexer = lexer::Lexer::new(input);
 
     assert_eq!(lexer.next_token(), Token::LBrace);
-    assert_eq!(
-        lexer.next_token(),
-        Token::StringLiteral("count".to_string())
-    );
+    assert_eq!(lexer.next_token(), Token::StringLiteral("count".into()));
     assert_eq!(lexer.next_token(), Token::Colon);
-    assert_eq!(lexer.next_token(), Token::NumberLiteral("42".to_string()));
+    assert_eq!(lexer.next_token(), Token::NumberLiteral("42".into()));
     assert_eq!(lexer.next_token(), Token::Comma);
-    assert_eq!(lexer.next_token(), Token::StringLiteral("name".to_string()));
+    assert_eq!(lexer.next_token(), Token::StringLiteral("name".into()));
     assert_eq!(lexer.next_token(), Token::Colon);
-    assert_eq!(lexer.next_token(), Token::StringLiteral("test".to_string()));
+    assert_eq!(lexer.next_token(), Token::StringLiteral("test".into()));
     assert_eq!(lexer.next_token(), Token::Comma);
-    assert_eq!(
-        lexer.next_token(),
-        Token::StringLiteral("active".to_string())
-    );
+    assert_eq!(lexer.next_token(), Token::StringLiteral("active".into()));
     assert_eq!(lexer.next_token(), Token::Colon);
     assert_eq!(lexer.next_token(), Token::BooleanLiteral(true));
     assert_eq!(lexer.next_token(), Token::RBrace);
 }
+
+#[test]
+fn test_spans() {
+    let input = "{\n  \"name\": \"Mía\",\n  \"ok\": tru\n}";
+    let mut l = lexer::Lexer::new(input);
+
+    let expected = [
+        (Token::LBrace, 0, 1, 1, 1),
+        (Token::StringLiteral("name".into()), 4, 10, 2, 3),
+        (Token::Colon, 10, 11, 2, 9),
+        (Token::StringLiteral("Mía".into()), 12, 18, 2, 11),
+        (Token::Comma, 18, 19, 2, 16),
+        (Token::StringLiteral("ok".into()), 22, 26, 3, 3),
+        (Token::Colon, 26, 27, 3, 7),
+        (Token::InvalidKeyword("tru".to_string()), 28, 31, 3, 9),
+        (Token::RBrace, 32, 33, 4, 1),
+    ];
+    for (token, start, end, line, column) in expected {
+        let spanned = l.next_spanned_token();
+        assert_eq!(spanned.token, token);
+        assert_eq!(
+            spanned.span,
+            Span {
+                start,
+                end,
+                line,
+                column
+            },
+            "span mismatch for {}",
+            token
+        );
+    }
+
+    let eof = l.next_spanned_token();
+    assert_eq!(eof.token, Token::EOF);
+    assert_eq!(eof.span.start, input.len());
+    assert_eq!(eof.span.end, input.len());
+}
+
+#[test]
+fn test_unicode_escapes() {
+    let test_cases = [
+        (r#""\u0041""#, "A"),
+        (r#""caf\u00e9""#, "café"),
+        (r#""\u00E9\u00e9""#, "éé"),
+        (r#""\u20AC 5""#, "€ 5"),
+        (r#""\ud83d\ude00""#, "😀"),
+        (r#""a\uD834\uDD1Eb""#, "a𝄞b"),
+        (r#""\u0000""#, "\u{0000}"),
+    ];
+
+    for (input, expected) in test_cases {
+        let mut l = lexer::Lexer::new(input);
+        assert_eq!(
+            l.next_token(),
+            Token::StringLiteral(expected.into()),
+            "Failed for input: {}",
+            input
+        );
+        assert_eq!(l.next_token(), Token::EOF);
+    }
+}
+
+#[test]
+fn test_invalid_escapes() {
+    let test_cases = [
+        (r#""\q""#, r"\q", 2),
+        (r#""ab\x41""#, r"\x", 4),
+        (r#""\u12G4""#, r"\u12", 2),
+        (r#""\u00""#, r"\u00", 2),
+        (r#""x\ud800""#, r"\ud800", 3),
+        (r#""\udc00\ud800""#, r"\udc00", 2),
+        (r#""\ud800A""#, r"\ud800", 2),
+        (r#""\ud800\ud800""#, r"\ud800", 2),
+    ];
+
+    for (input, escape, column) in test_cases {
+        let mut l = lexer::Lexer::new(input);
+        let spanned = l.next_spanned_token();
+        assert_eq!(
+            spanned.token,
+            Token::InvalidEscape(escape.to_string()),
+            "Failed for input: {}",
+            input
+        );
+        assert_eq!(spanned.span.column, column, "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_lone_surrogate_policies() {
+    let test_cases = [
+        (LoneSurrogatePolicy::Replace, r#""a\ud800b""#, "a\u{FFFD}b"),
+        (LoneSurrogatePolicy::Replace, r#""\udc00😀""#, "\u{FFFD}😀"),
+        (
+            LoneSurrogatePolicy::Replace,
+            r#""\ud800\ud800""#,
+            "\u{FFFD}\u{FFFD}",
+        ),
+    ];
+
+    for (policy, input, expected) in test_cases {
+        let mut l = lexer::Lexer::new(input).with_lone_surrogate_policy(policy);
+        assert_eq!(
+            l.next_token(),
+            Token::StringLiteral(expected.into()),
+            "Failed for input: {}",
+            input
+        );
+        assert_eq!(l.next_token(), Token::EOF);
+        assert_eq!(l.wtf8(), None);
+    }
+
+    // preserved surrogates are told apart from an escaped U+FFFD
+    let input = r#""a\ud800b \ufffd\udc00""#;
+    let mut l = lexer::Lexer::new(input).with_lone_surrogate_policy(LoneSurrogatePolicy::Preserve);
+    assert_eq!(
+        l.next_token(),
+        Token::StringLiteral("a\u{FFFD}b \u{FFFD}\u{FFFD}".into())
+    );
+    assert_eq!(
+        l.wtf8(),
+        Some(&b"a\xed\xa0\x80b \xef\xbf\xbd\xed\xb0\x80"[..])
+    );
+    assert_eq!(l.next_token(), Token::EOF);
+    assert_eq!(l.wtf8(), None);
+
+    let mut l =
+        lexer::Lexer::new(r#""plain""#).with_lone_surrogate_policy(LoneSurrogatePolicy::Preserve);
+    assert_eq!(l.next_token(), Token::StringLiteral("plain".into()));
+    assert_eq!(l.wtf8(), None);
+}
+
+#[test]
+fn test_invalid_numbers() {
+    let test_cases = [
+        "-", "01", "-01", "00", "1.", "1e", "1e+", "-.5", "1.e5", "1.5.2", "1e5e5", "--1", "1-2",
+    ];
+
+    for input in test_cases {
+        let mut l = lexer::Lexer::new(input);
+        let spanned = l.next_spanned_token();
+        assert_eq!(
+            spanned.token,
+            Token::InvalidNumber(input.to_string()),
+            "Failed for input: {}",
+            input
+        );
+        assert_eq!((spanned.span.start, spanned.span.end), (0, input.len()));
+        assert_eq!(l.next_token(), Token::EOF);
+    }
+}
+
+#[test]
+fn test_invalid_number_boundaries() {
+    let input = "[1., 2]";
+    let mut l = lexer::Lexer::new(input);
+    assert_eq!(l.next_token(), Token::LSquare);
+    assert_eq!(l.next_token(), Token::InvalidNumber("1.".to_string()));
+    assert_eq!(l.next_token(), Token::Comma);
+    assert_eq!(l.next_token(), Token::NumberLiteral("2".into()));
+    assert_eq!(l.next_token(), Token::RSquare);
+}
+
+#[test]
+fn test_unterminated_string() {
+    let test_cases = [r#""abc"#, r#""abc\"#, r#""abc\""#, r#"""#];
+
+    for input in test_cases {
+        let mut l = lexer::Lexer::new(input);
+        let spanned = l.next_spanned_token();
+        assert_eq!(
+            spanned.token,
+            Token::UnterminatedString,
+            "Failed for input: {}",
+            input
+        );
+        assert_eq!((spanned.span.start, spanned.span.end), (0, input.len()));
+        assert_eq!(l.next_token(), Token::EOF);
+    }
+}
+
+#[test]
+fn test_control_character_in_string() {
+    let input = "[\"ok\", \"line\nbreak\"]";
+    let mut l = lexer::Lexer::new(input);
+    assert_eq!(l.next_token(), Token::LSquare);
+    assert_eq!(l.next_token(), Token::StringLiteral("ok".into()));
+    assert_eq!(l.next_token(), Token::Comma);
+
+    let spanned = l.next_spanned_token();
+    assert_eq!(spanned.token, Token::ControlCharacterInString('\n'));
+    assert_eq!((spanned.span.start, spanned.span.column), (12, 13));
+
+    let mut l = lexer::Lexer::new("\"tab\tnul\u{0}\"");
+    assert_eq!(l.next_token(), Token::ControlCharacterInString('\t'));
+}
+
+#[test]
+fn test_utf8_input() {
+    // offsets count bytes, columns count characters
+    let input = "[\"é🦀\", €]";
+    let tokens: Vec<_> = lexer::Lexer::new(input)
+        .map(|t| (t.token, t.span.start, t.span.end, t.span.column))
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            (Token::LSquare, 0, 1, 1),
+            (Token::StringLiteral("é🦀".into()), 1, 9, 2),
+            (Token::Comma, 9, 10, 6),
+            (Token::Illegal('€'), 11, 14, 8),
+            (Token::RSquare, 14, 15, 9),
+        ]
+    );
+
+    let cases: [(&[u8], usize); 5] = [
+        (b"\"a\xffb\"", 2),
+        (b"\"\xc3\"", 1),
+        (b"\"\xc0\xaf\"", 1),
+        (b"\"\xed\xa0\x80\"", 1),
+        (b"  \xf5", 2),
+    ];
+    for (input, offset) in cases {
+        let mut l = lexer::Lexer::from_slice(input);
+        let spanned = l.next_spanned_token();
+        assert_eq!(
+            spanned.token,
+            Token::InvalidUtf8,
+            "Failed for input: {:?}",
+            input
+        );
+        assert_eq!(spanned.span.start, offset, "Failed for input: {:?}", input);
+    }
+}
+
+#[test]
+fn test_borrowed_tokens() {
+    let input = r#"["plain", "ünïcode", "esc\naped", -12.5e3]"#;
+    let borrowed = |t: &Token| match t {
+        Token::StringLiteral(s) | Token::NumberLiteral(s) => Some(matches!(s, Cow::Borrowed(_))),
+        _ => None,
+    };
+
+    let tokens: Vec<_> = lexer::Lexer::new(input).map(|t| t.token).collect();
+    let kinds: Vec<_> = tokens.iter().filter_map(borrowed).collect();
+    assert_eq!(kinds, [true, true, false, true]);
+    assert_eq!(tokens[5], Token::StringLiteral("esc\naped".into()));
+
+    // a reader can't lend its buffer, so everything is copied
+    let tokens: Vec<_> = lexer::Lexer::from_reader(input.as_bytes())
+        .map(|t| t.token)
+        .collect();
+    let kinds: Vec<_> = tokens.iter().filter_map(borrowed).collect();
+    assert_eq!(kinds, [false, false, false, false]);
+    assert_eq!(tokens[7], Token::NumberLiteral("-12.5e3".into()));
+
+    // tokens after invalid UTF-8 are still borrowed whole
+    let tokens: Vec<_> = lexer::Lexer::from_slice(b"\xff \"abc\" 12")
+        .map(|t| t.token)
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            Token::InvalidUtf8,
+            Token::StringLiteral(Cow::Borrowed("abc")),
+            Token::NumberLiteral(Cow::Borrowed("12"))
+        ]
+    );
+}
@@ -0,0 +1,191 @@
+use rijson::{
+    map::Map,
+    parser::{JsonValue, Parser},
+};
+
+fn parse(input: &str) -> JsonValue {
+    Parser::new(input).parse().unwrap()
+}
+
+#[test]
+#[cfg(not(feature = "sorted_keys"))]
+fn test_object_keeps_insertion_order() {
+    let input = r#"{"zeta": 1, "alpha": {"y": true, "b": null, "x": []}, "mid": "m"}"#;
+    let value = parse(input);
+    assert_eq!(
+        value.to_string(),
+        r#"{"zeta": 1, "alpha": {"y": true, "b": null, "x": []}, "mid": "m"}"#
+    );
+}
+
+#[test]
+#[cfg(feature = "sorted_keys")]
+fn test_object_keeps_keys_sorted() {
+    let input = r#"{"zeta": 1, "alpha": {"y": true, "b": null, "x": []}, "mid": "m"}"#;
+    let value = parse(input);
+    assert_eq!(
+        value.to_string(),
+        r#"{"alpha": {"b": null, "x": [], "y": true}, "mid": "m", "zeta": 1}"#
+    );
+}
+
+#[test]
+fn test_insert_replaces_in_place() {
+    let mut map = Map::new();
+    assert_eq!(map.insert("b".to_string(), JsonValue::Null), None);
+    assert_eq!(map.insert("a".to_string(), JsonValue::Boolean(true)), None);
+    assert_eq!(
+        map.insert("b".to_string(), JsonValue::Boolean(false)),
+        Some(JsonValue::Null)
+    );
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map.get("b"), Some(&JsonValue::Boolean(false)));
+    assert!(map.contains_key("a"));
+    assert!(!map.contains_key("c"));
+
+    let keys: Vec<&String> = map.iter().map(|(k, _)| k).collect();
+    if cfg!(feature = "sorted_keys") {
+        assert_eq!(keys, ["a", "b"]);
+    } else {
+        assert_eq!(keys, ["b", "a"]);
+    }
+}
+
+#[test]
+fn test_object_equality_ignores_order() {
+    assert_eq!(
+        parse(r#"{"a": 1, "b": [2]}"#),
+        parse(r#"{"b": [2], "a": 1}"#)
+    );
+    assert_ne!(
+        parse(r#"{"a": 1, "b": [2]}"#),
+        parse(r#"{"a": 1, "b": [3]}"#)
+    );
+    assert_ne!(parse(r#"{"a": 1}"#), parse(r#"{"a": 1, "b": 2}"#));
+}
+
+#[test]
+fn test_remove_keeps_order() {
+    let JsonValue::Object(mut map) = parse(r#"{"a": 1, "b": 2, "c": 3, "d": 4}"#) else {
+        panic!("expected an object");
+    };
+    assert_eq!(map.remove("b"), Some(JsonValue::Number(2.into())));
+    assert_eq!(map.remove("b"), None);
+    assert_eq!(map.get("c"), Some(&JsonValue::Number(3.into())));
+    assert_eq!(map.keys().collect::<Vec<_>>(), ["a", "c", "d"]);
+
+    // a repeated key goes entirely
+    map.append("c".to_string(), JsonValue::Null);
+    assert_eq!(map.remove("c"), Some(JsonValue::Number(3.into())));
+    assert_eq!(map.get_all("c").count(), 0);
+    assert_eq!(map.get("d"), Some(&JsonValue::Number(4.into())));
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_entry() {
+    let mut map = Map::new();
+    *map.entry("count").or_insert(JsonValue::Number(0.into())) = JsonValue::Number(1.into());
+    let count = map
+        .entry("count")
+        .or_insert_with(|| panic!("count is present"));
+    assert_eq!(*count, JsonValue::Number(1.into()));
+
+    map.entry("tags")
+        .or_insert_with(|| JsonValue::Array(vec![]))
+        .push(JsonValue::String("new".to_string()));
+    assert_eq!(map.entry("tags").key(), "tags");
+    assert_eq!(
+        map.get("tags")
+            .and_then(|t| t.get_index(0))
+            .and_then(JsonValue::as_str),
+        Some("new")
+    );
+    assert_eq!(map.len(), 2);
+}
+
+#[test]
+fn test_retain_and_sort_keys() {
+    let JsonValue::Object(mut map) = parse(r#"{"zeta": 1, "alpha": null, "mid": 2, "beta": null}"#)
+    else {
+        panic!("expected an object");
+    };
+    map.retain(|_, v| !v.is_null());
+    assert_eq!(map.len(), 2);
+    assert!(!map.contains_key("alpha"));
+    assert_eq!(map.get("mid"), Some(&JsonValue::Number(2.into())));
+
+    map.insert("beta".to_string(), JsonValue::Boolean(true));
+    map.sort_keys();
+    assert_eq!(map.keys().collect::<Vec<_>>(), ["beta", "mid", "zeta"]);
+    assert_eq!(map.get("zeta"), Some(&JsonValue::Number(1.into())));
+
+    for v in map.values_mut() {
+        *v = JsonValue::Null;
+    }
+    assert!(map.values().all(JsonValue::is_null));
+}
+
+#[test]
+fn test_many_keys_and_repeated_keys() {
+    let mut map = Map::new();
+    for i in 0..1000 {
+        map.insert(format!("k{i:04}"), JsonValue::Number(i.into()));
+    }
+    map.append("k0001".to_string(), JsonValue::Null);
+    map.append("k0001".to_string(), JsonValue::Boolean(true));
+    assert_eq!(map.len(), 1002);
+    assert_eq!(map.get("k0999"), Some(&JsonValue::Number(999.into())));
+    assert_eq!(map.get("k1000"), None);
+    assert_eq!(
+        map.get_all("k0001").collect::<Vec<_>>(),
+        [
+            &JsonValue::Number(1.into()),
+            &JsonValue::Null,
+            &JsonValue::Boolean(true)
+        ]
+    );
+
+    let keys: Vec<&String> = map.keys().collect();
+    if cfg!(feature = "sorted_keys") {
+        assert_eq!(keys[..4], ["k0000", "k0001", "k0001", "k0001"]);
+    } else {
+        assert_eq!(keys[998..], ["k0998", "k0999", "k0001", "k0001"]);
+    }
+
+    map.retain(|k, _| k < "k0002");
+    let pairs: Vec<(String, JsonValue)> = map.into_iter().collect();
+    assert_eq!(pairs.len(), 4);
+    assert_eq!(pairs[0], ("k0000".to_string(), JsonValue::Number(0.into())));
+}
+
+#[test]
+fn test_remove_many_keys() {
+    let mut map = Map::new();
+    for i in 0..500 {
+        map.insert(format!("k{i:03}"), JsonValue::Number(i.into()));
+    }
+    for i in (0..500).step_by(3) {
+        assert_eq!(
+            map.remove(&format!("k{i:03}")),
+            Some(JsonValue::Number(i.into())),
+            "Failed for input: {}",
+            i
+        );
+    }
+    for i in 0..500 {
+        let expected = (i % 3 != 0).then(|| JsonValue::Number(i.into()));
+        assert_eq!(
+            map.get(&format!("k{i:03}")),
+            expected.as_ref(),
+            "Failed for input: {}",
+            i
+        );
+    }
+    assert_eq!(map.len(), 333);
+    assert_eq!(
+        map.keys().take(3).collect::<Vec<_>>(),
+        ["k001", "k002", "k004"]
+    );
+}
@@ -0,0 +1,104 @@
+use rijson::{
+    number::{JsonNumber, NumberError},
+    parser::{JsonValue, Parser},
+};
+
+fn number(text: &str) -> JsonNumber {
+    text.parse().unwrap()
+}
+
+#[test]
+#[cfg(not(feature = "arbitrary_precision"))]
+fn test_classification() {
+    let cases = [
+        ("0", true),
+        ("-12", true),
+        ("12345678901234567890123", true),
+        ("1.0", false),
+        ("1e3", false),
+        ("-0.5E-2", false),
+        ("NaN", false),
+        ("-Infinity", false),
+    ];
+    for (input, integer) in cases {
+        let n = number(input);
+        assert_eq!(n.is_integer(), integer, "Failed for input: {}", input);
+        assert_eq!(n.is_float(), !integer, "Failed for input: {}", input);
+        assert_eq!(n.as_str(), input, "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_integer_conversions() {
+    assert_eq!(number("-0").as_u64(), Ok(0));
+    assert_eq!(number("9007199254740993").as_i64(), Ok(9007199254740993));
+    assert_eq!(number("9223372036854775807").as_i64(), Ok(i64::MAX));
+    assert_eq!(
+        number("9223372036854775808").as_i64(),
+        Err(NumberError::OutOfRange)
+    );
+    assert_eq!(number("18446744073709551615").as_u64(), Ok(u64::MAX));
+    assert_eq!(
+        number("18446744073709551616").as_u64(),
+        Err(NumberError::OutOfRange)
+    );
+    assert_eq!(number("-1").as_u64(), Err(NumberError::OutOfRange));
+    assert_eq!(
+        number("-170141183460469231731687303715884105728").as_i128(),
+        Ok(i128::MIN)
+    );
+    assert_eq!(
+        number("170141183460469231731687303715884105728").as_i128(),
+        Err(NumberError::OutOfRange)
+    );
+    if cfg!(not(feature = "arbitrary_precision")) {
+        assert_eq!(number("1.0").as_i64(), Err(NumberError::NotAnInteger));
+        assert_eq!(number("1e2").as_u64(), Err(NumberError::NotAnInteger));
+    }
+}
+
+#[test]
+fn test_float_conversions() {
+    assert_eq!(number("1.5").as_f64(), Ok(1.5));
+    assert_eq!(number("-1e-10").as_f64(), Ok(-1e-10));
+    assert_eq!(number("42").as_f64(), Ok(42.0));
+    assert_eq!(number("1e400").as_f64(), Err(NumberError::OutOfRange));
+    assert_eq!(number("1e-400").as_f64(), Ok(0.0));
+    assert_eq!(number("-Infinity").as_f64(), Ok(f64::NEG_INFINITY));
+    assert!(number("NaN").as_f64().unwrap().is_nan());
+}
+
+#[test]
+fn test_constructors() {
+    assert_eq!(JsonNumber::from(-7i32), number("-7"));
+    assert_eq!(JsonNumber::from(u64::MAX).as_str(), "18446744073709551615");
+    assert_eq!(JsonNumber::from_f64(0.25), Some(number("0.25")));
+    assert_eq!(JsonNumber::from_f64(f64::NAN), None);
+
+    for input in ["", "01", "1.", "+1", "nan", "--1", "1e"] {
+        assert_eq!(
+            input.parse::<JsonNumber>(),
+            Err(NumberError::Invalid),
+            "Failed for input: {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_parsed_numbers_are_lossless() {
+    let input = r#"[9007199254740993, 12345678901234567890.000000000001, 1.0e+10]"#;
+    let value = Parser::new(input).parse().unwrap();
+    if cfg!(not(feature = "arbitrary_precision")) {
+        assert_eq!(value.to_string(), input);
+    }
+
+    let JsonValue::Array(items) = value else {
+        panic!("expected an array");
+    };
+    let JsonValue::Number(id) = &items[0] else {
+        panic!("expected a number");
+    };
+    assert_eq!(id.as_u64(), Ok(9007199254740993));
+    assert_eq!(id.as_f64(), Ok(9007199254740992.0));
+}
@@ -0,0 +1,178 @@
+use rijson::{
+    error::ParseErrorKind,
+    limits::Limits,
+    options::{NumberRepresentation, ParserOptions},
+    parser::{DuplicateKeyPolicy, JsonValue, Parser},
+};
+
+#[test]
+fn test_strict_by_default() {
+    let cases = [
+        ("[1] // done", ParseErrorKind::TrailingContent),
+        ("/* a */ [1]", ParseErrorKind::IllegalCharacter('/')),
+        ("[1,]", ParseErrorKind::TrailingComma),
+        ("['a']", ParseErrorKind::IllegalCharacter('\'')),
+        ("[NaN]", ParseErrorKind::InvalidKeyword("NaN".to_string())),
+        (
+            "[-Infinity]",
+            ParseErrorKind::InvalidNumber("-".to_string()),
+        ),
+    ];
+    let options = ParserOptions::new();
+    for (input, kind) in cases {
+        let err = Parser::new(input)
+            .with_options(&options)
+            .parse()
+            .err()
+            .unwrap();
+        assert_eq!(err.kind, kind, "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_lenient() {
+    let input = r#"
+        // service settings
+        {
+            'name': 'it\'s "quoted"', /* inline */
+            "ratio": NaN,
+            "range": [-Infinity, Infinity,],
+            "url": "http://example.com/*not a comment*/",
+        }
+    "#;
+    let value = Parser::new(input)
+        .with_options(&ParserOptions::lenient())
+        .parse()
+        .unwrap();
+
+    let JsonValue::Object(obj) = &value else {
+        panic!("expected an object, got {}", value);
+    };
+    assert_eq!(obj.len(), 4);
+    assert_eq!(
+        obj.get("name"),
+        Some(&JsonValue::String("it's \"quoted\"".to_string()))
+    );
+    assert_eq!(
+        obj.get("ratio"),
+        Some(&JsonValue::Number("NaN".parse().unwrap()))
+    );
+    assert_eq!(
+        obj.get("range"),
+        Some(&JsonValue::Array(vec![
+            JsonValue::Number("-Infinity".parse().unwrap()),
+            JsonValue::Number("Infinity".parse().unwrap()),
+        ]))
+    );
+    assert_eq!(
+        obj.get("url"),
+        Some(&JsonValue::String(
+            "http://example.com/*not a comment*/".to_string()
+        ))
+    );
+
+    let cases = [
+        ("[1] /* open", ParseErrorKind::UnterminatedComment, 4),
+        (
+            "[-Infinit]",
+            ParseErrorKind::InvalidNumber("-Infinit".to_string()),
+            1,
+        ),
+        ("['a\"]", ParseErrorKind::UnterminatedString, 1),
+    ];
+    for (input, kind, start) in cases {
+        let err = Parser::new(input)
+            .with_options(&ParserOptions::lenient())
+            .parse()
+            .err()
+            .unwrap();
+        assert_eq!(err.kind, kind, "Failed for input: {}", input);
+        assert_eq!(err.span.start, start, "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_options_are_reusable() {
+    let options = ParserOptions::new()
+        .allow_scalar_root(false)
+        .with_duplicate_key_policy(DuplicateKeyPolicy::Error)
+        .with_limits(Limits {
+            max_array_elements: Some(2),
+            ..Limits::default()
+        })
+        .with_max_depth(Some(2));
+
+    let cases = [
+        (
+            "42",
+            Some(ParseErrorKind::UnexpectedToken("42".to_string())),
+        ),
+        (
+            "\"a\"",
+            Some(ParseErrorKind::UnexpectedToken("\"a\"".to_string())),
+        ),
+        ("[1, 2, 3]", Some(ParseErrorKind::TooManyElements(2))),
+        ("[[[]]]", Some(ParseErrorKind::DepthLimitExceeded(2))),
+        ("[\"scalars are fine inside\", 1]", None),
+        ("{\"a\": 1}", None),
+    ];
+    for (input, kind) in cases {
+        let result = Parser::new(input).with_options(&options).parse();
+        assert_eq!(
+            result.err().map(|e| e.kind),
+            kind,
+            "Failed for input: {}",
+            input
+        );
+    }
+
+    let err = Parser::new(r#"{"a": 1, "a": 2}"#)
+        .with_options(&options)
+        .parse()
+        .err()
+        .unwrap();
+    assert!(matches!(err.kind, ParseErrorKind::DuplicateKey { .. }));
+}
+
+#[test]
+fn test_number_representation() {
+    let cases = [
+        ("1.0e+10", "10000000000.0"),
+        ("1e-10", "1e-10"),
+        ("0.1", "0.1"),
+        ("-0", "-0.0"),
+        ("1", "1"),
+        ("-42", "-42"),
+        ("9007199254740991", "9007199254740991"),
+        ("9007199254740993", "9007199254740992.0"),
+        ("1152921504606846976", "1.152921504606847e18"),
+        ("12345678901234567890", "1.2345678901234567e19"),
+        ("1e400", "1e400"),
+    ];
+    let options = ParserOptions::new().with_number_representation(NumberRepresentation::F64);
+    for (input, expected) in cases {
+        let value = Parser::new(input).with_options(&options).parse().unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Number(expected.parse().unwrap()),
+            "Failed for input: {}",
+            input
+        );
+
+        let value = Parser::new(input).parse().unwrap();
+        assert_eq!(
+            value,
+            JsonValue::Number(input.parse().unwrap()),
+            "Failed for input: {}",
+            input
+        );
+    }
+
+    let value = Parser::new("[1, -42]")
+        .with_options(&options)
+        .parse()
+        .unwrap();
+    let as_i64 = |i: usize| value[i].as_number().unwrap().as_i64();
+    assert_eq!(as_i64(0), Ok(1));
+    assert_eq!(as_i64(1), Ok(-42));
+}
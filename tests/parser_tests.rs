@@ -0,0 +1,998 @@
+use std::{borrow::Cow, io::Read};
+
+use rijson::{
+    error::{ParseErrorKind, PathSegment},
+    limits::Limits,
+    parser::{DuplicateKeyPolicy, JsonValue, JsonValueRef, Parser},
+    visitor::JsonVisitor,
+};
+
+#[test]
+fn test_error_reports_position() {
+    let input = "{\n  \"a\": 1,\n  \"b\": tru\n}";
+    let mut p = Parser::new(input);
+    let err = p.parse().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::InvalidKeyword("tru".to_string()));
+    assert_eq!((err.span.line, err.span.column), (3, 8));
+    assert_eq!(
+        err.to_string(),
+        "invalid keyword tru at line 3, column 8 (in /b)"
+    );
+}
+
+#[test]
+fn test_error_kinds() {
+    let cases = [
+        ("", ParseErrorKind::UnexpectedEof),
+        ("[1, 2", ParseErrorKind::UnexpectedEof),
+        ("[1 2]", ParseErrorKind::MissingComma),
+        ("{\"a\"}", ParseErrorKind::MissingColon),
+        ("[1, $]", ParseErrorKind::IllegalCharacter('$')),
+        ("[1, :]", ParseErrorKind::UnexpectedToken(":".to_string())),
+        (
+            "{\"a\": 1]",
+            ParseErrorKind::UnexpectedToken("]".to_string()),
+        ),
+    ];
+
+    for (input, kind) in cases {
+        let mut p = Parser::new(input);
+        let err = p.parse().err().unwrap();
+        assert_eq!(err.kind, kind, "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_error_path() {
+    let input = r#"{"users": [{"name": "a"}, {"name": $}]}"#;
+    let mut p = Parser::new(input);
+    let err = p.parse().err().unwrap();
+    assert_eq!(
+        err.path.0,
+        vec![
+            PathSegment::Key("users".to_string()),
+            PathSegment::Index(1),
+            PathSegment::Key("name".to_string()),
+        ]
+    );
+    assert_eq!(err.path.to_string(), "/users/1/name");
+}
+
+#[test]
+fn test_error_is_std_error() {
+    let mut p = Parser::new("[");
+    let err: Box<dyn std::error::Error> = Box::new(p.parse().err().unwrap());
+    assert_eq!(
+        err.to_string(),
+        "unexpected end of input at line 1, column 2 (in /0)"
+    );
+}
+
+#[test]
+fn test_invalid_escape_error() {
+    let mut p = Parser::new(r#"{"key": "bad \q escape"}"#);
+    let err = p.parse().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::InvalidEscape(r"\q".to_string()));
+    assert_eq!(err.span.column, 14);
+}
+
+#[test]
+fn test_invalid_number_error() {
+    let mut p = Parser::new(r#"{"id": 007}"#);
+    let err = p.parse().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::InvalidNumber("007".to_string()));
+    assert_eq!((err.span.start, err.span.end), (7, 10));
+}
+
+#[test]
+fn test_truncated_string_error() {
+    let mut p = Parser::new("[\"complete\", \"trunc");
+    let err = p.parse().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::UnterminatedString);
+    assert_eq!(err.span.column, 14);
+    assert_eq!(err.path.to_string(), "/1");
+}
+
+#[test]
+fn test_separator_errors() {
+    let cases = [
+        (
+            "[,,1,,]",
+            ParseErrorKind::UnexpectedToken(",".to_string()),
+            2,
+        ),
+        (
+            "[1,,2]",
+            ParseErrorKind::UnexpectedToken(",".to_string()),
+            4,
+        ),
+        ("[1,]", ParseErrorKind::TrailingComma, 3),
+        ("[1, [2,], 3]", ParseErrorKind::TrailingComma, 7),
+        ("[,]", ParseErrorKind::UnexpectedToken(",".to_string()), 2),
+        ("[1 [2]]", ParseErrorKind::MissingComma, 4),
+        ("{\"a\":1,}", ParseErrorKind::TrailingComma, 7),
+        (
+            "{,\"a\":1}",
+            ParseErrorKind::UnexpectedToken(",".to_string()),
+            2,
+        ),
+        ("{\"a\" 1}", ParseErrorKind::MissingColon, 6),
+        (
+            "{\"a\"::1}",
+            ParseErrorKind::UnexpectedToken(":".to_string()),
+            6,
+        ),
+        ("{\"a\":1 \"b\":2}", ParseErrorKind::MissingComma, 8),
+        (
+            "{\"a\":}",
+            ParseErrorKind::UnexpectedToken("}".to_string()),
+            6,
+        ),
+        ("{1:2}", ParseErrorKind::UnexpectedToken("1".to_string()), 2),
+        (
+            "{\"a\":1,,\"b\":2}",
+            ParseErrorKind::UnexpectedToken(",".to_string()),
+            8,
+        ),
+    ];
+
+    for (input, kind, column) in cases {
+        let mut p = Parser::new(input);
+        let err = p.parse().err().unwrap();
+        assert_eq!(err.kind, kind, "Failed for input: {}", input);
+        assert_eq!(err.span.column, column, "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_allow_trailing_commas() {
+    let input = "[1, [2, 3,], {\"a\": [],},]";
+    let mut p = Parser::new(input).allow_trailing_commas(true);
+    assert_eq!(p.parse().unwrap().to_string(), "[1, [2, 3], {\"a\": []}]");
+
+    // only one trailing comma is tolerated, never a leading or doubled one
+    for input in ["[1,,]", "[,]", "{,}", "{\"a\": 1,,}"] {
+        let mut p = Parser::new(input).allow_trailing_commas(true);
+        assert!(p.parse().is_err(), "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_empty_containers() {
+    for input in ["[]", "{}", "[[], {}]", "{\"a\": {}, \"b\": []}"] {
+        let mut p = Parser::new(input);
+        assert!(p.parse().is_ok(), "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_iterator_rejects_separator_errors() {
+    let p = Parser::new("[{\"a\": 1},, {\"b\": 2}]");
+    let results: Vec<_> = p.collect();
+    assert_eq!(results.len(), 2);
+    assert!(results[0].is_ok());
+    assert_eq!(
+        results[1].as_ref().err().unwrap().kind,
+        ParseErrorKind::UnexpectedToken(",".to_string())
+    );
+}
+
+#[test]
+fn test_duplicate_key_policies() {
+    let input = r#"{"role": "user", "id": 1, "role": "admin"}"#;
+    let parse = |policy| Parser::new(input).with_duplicate_key_policy(policy).parse();
+
+    let expected = |input: &str| Parser::new(input).parse().unwrap();
+
+    let value = parse(DuplicateKeyPolicy::LastWins).unwrap();
+    assert_eq!(value, expected(r#"{"role": "admin", "id": 1}"#));
+
+    let value = parse(DuplicateKeyPolicy::FirstWins).unwrap();
+    assert_eq!(value, expected(r#"{"role": "user", "id": 1}"#));
+
+    let value = parse(DuplicateKeyPolicy::KeepAll).unwrap();
+    let JsonValue::Object(obj) = &value else {
+        panic!("expected an object");
+    };
+    assert_eq!(obj.len(), 3);
+    assert_eq!(
+        obj.get("role"),
+        Some(&JsonValue::String("user".to_string()))
+    );
+    let roles: Vec<_> = obj.get_all("role").map(|v| v.to_string()).collect();
+    assert_eq!(roles, [r#""user""#, r#""admin""#]);
+}
+
+#[test]
+fn test_duplicate_key_error() {
+    let input = "{\n  \"sub\": \"alice\",\n  \"claims\": {\"admin\": false, \"admin\": true}\n}";
+    let mut p = Parser::new(input).with_duplicate_key_policy(DuplicateKeyPolicy::Error);
+    let err = p.parse().err().unwrap();
+
+    let ParseErrorKind::DuplicateKey { key, first } = &err.kind else {
+        panic!("expected a duplicate key error, got {err}");
+    };
+    assert_eq!(key, "admin");
+    assert_eq!((first.line, first.column), (3, 14));
+    assert_eq!((err.span.line, err.span.column), (3, 30));
+    assert_eq!(err.path.to_string(), "/claims");
+
+    let mut p = Parser::new(r#"{"a": {"b": 1}, "b": {"a": 2}}"#)
+        .with_duplicate_key_policy(DuplicateKeyPolicy::Error);
+    assert!(p.parse().is_ok());
+}
+
+#[test]
+fn test_scalar_root() {
+    let cases = [
+        (r#""hello""#, JsonValue::String("hello".to_string())),
+        ("42", JsonValue::Number("42".parse().unwrap())),
+        ("-1.5e3", JsonValue::Number("-1.5e3".parse().unwrap())),
+        ("true", JsonValue::Boolean(true)),
+        ("false", JsonValue::Boolean(false)),
+        ("  null\n", JsonValue::Null),
+    ];
+
+    for (input, expected) in cases {
+        let mut p = Parser::new(input);
+        assert_eq!(p.parse().unwrap(), expected, "Failed for input: {}", input);
+    }
+
+    for (input, kind) in [
+        ("nul", ParseErrorKind::InvalidKeyword("nul".to_string())),
+        (":", ParseErrorKind::UnexpectedToken(":".to_string())),
+        ("]", ParseErrorKind::UnexpectedToken("]".to_string())),
+    ] {
+        let mut p = Parser::new(input);
+        assert_eq!(
+            p.parse().err().unwrap().kind,
+            kind,
+            "Failed for input: {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_trailing_content() {
+    let cases = [
+        (r#"{"a":1} xyz"#, 9),
+        ("[1]]", 4),
+        ("42 43", 4),
+        ("null\n\n  ,", 3),
+    ];
+
+    for (input, column) in cases {
+        let mut p = Parser::new(input);
+        let err = p.parse().err().unwrap();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::TrailingContent,
+            "Failed for input: {}",
+            input
+        );
+        assert_eq!(err.span.column, column, "Failed for input: {}", input);
+    }
+
+    let mut p = Parser::new(" [1, 2] \n\t");
+    assert!(p.parse().is_ok());
+}
+
+#[test]
+fn test_parse_prefix() {
+    let input = r#"{"id": 1}{"id": 2} [3]  "four" 5"#;
+    let mut p = Parser::new(input);
+
+    let (value, offset) = p.parse_prefix().unwrap();
+    assert_eq!(value.to_string(), r#"{"id": 1}"#);
+    assert_eq!(&input[offset..], r#"{"id": 2} [3]  "four" 5"#);
+
+    let mut values = vec![value.to_string()];
+    while !p.is_at_end() {
+        let (value, offset) = p.parse_prefix().unwrap();
+        values.push(value.to_string());
+        assert!(!input[..offset].ends_with(' '));
+    }
+    assert_eq!(
+        values,
+        [r#"{"id": 1}"#, r#"{"id": 2}"#, "[3]", r#""four""#, "5"]
+    );
+
+    // a number cut short by the source is an error, not a shorter number
+    let err = Parser::new("123456")
+        .with_limits(Limits {
+            max_input_bytes: Some(3),
+            ..Limits::default()
+        })
+        .parse_prefix()
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, ParseErrorKind::InputTooLarge(3));
+
+    let reader = FailsAfter { input: b"12" };
+    let err = Parser::from_reader(reader).parse_prefix().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::Io("connection reset".to_string()));
+}
+
+/// hands out its input, then fails
+struct FailsAfter<'a> {
+    input: &'a [u8],
+}
+
+impl std::io::Read for FailsAfter<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.input.is_empty() {
+            return Err(std::io::Error::other("connection reset"));
+        }
+        let n = buf.len().min(self.input.len());
+        buf[..n].copy_from_slice(&self.input[..n]);
+        self.input = &self.input[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_stream_array_elements() {
+    let input = r#"[1, "x", [2, {"a": null}], {"b": true}, false]"#;
+    let p = Parser::new(input);
+    let values: Vec<String> = p.map(|v| v.unwrap().to_string()).collect();
+    assert_eq!(
+        values,
+        [
+            "1",
+            r#""x""#,
+            r#"[2, {"a": null}]"#,
+            r#"{"b": true}"#,
+            "false"
+        ]
+    );
+
+    for input in ["[]", "", "  "] {
+        let mut p = Parser::new(input);
+        assert!(p.next().is_none(), "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_stream_array_errors() {
+    let p = Parser::new("[1, 2 3]");
+    let results: Vec<_> = p.collect();
+    assert_eq!(results.len(), 3);
+    let err = results[2].as_ref().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::MissingComma);
+
+    let p = Parser::new("[1, [2, $]]");
+    let results: Vec<_> = p.collect();
+    let err = results[1].as_ref().err().unwrap();
+    assert_eq!(err.path.to_string(), "/1/1");
+
+    let p = Parser::new(r#"{"a": 1}"#);
+    let results: Vec<_> = p.collect();
+    assert_eq!(
+        results[0].as_ref().err().unwrap().kind,
+        ParseErrorKind::UnexpectedToken("{".to_string())
+    );
+
+    let p = Parser::new("[1] 2");
+    let results: Vec<_> = p.collect();
+    assert_eq!(results.len(), 2);
+    assert_eq!(
+        results[1].as_ref().err().unwrap().kind,
+        ParseErrorKind::TrailingContent
+    );
+}
+
+#[test]
+fn test_stream_object_entries() {
+    let input = r#"{"meta": {"v": 1}, "items": [1, 2], "ok": true}"#;
+    let entries: Vec<(String, String)> = Parser::new(input)
+        .into_entries()
+        .map(|e| e.map(|(k, v)| (k, v.to_string())).unwrap())
+        .collect();
+    assert_eq!(
+        entries,
+        [
+            ("meta".to_string(), r#"{"v": 1}"#.to_string()),
+            ("items".to_string(), "[1, 2]".to_string()),
+            ("ok".to_string(), "true".to_string()),
+        ]
+    );
+
+    assert!(Parser::new("{}").into_entries().next().is_none());
+
+    let results: Vec<_> = Parser::new(r#"{"a": 1, "a": 2}"#)
+        .with_duplicate_key_policy(DuplicateKeyPolicy::Error)
+        .into_entries()
+        .collect();
+    assert_eq!(results.len(), 2);
+    assert!(matches!(
+        results[1].as_ref().err().unwrap().kind,
+        ParseErrorKind::DuplicateKey { .. }
+    ));
+
+    let results: Vec<_> = Parser::new(r#"{"a": [1, }"#).into_entries().collect();
+    let err = results[0].as_ref().err().unwrap();
+    assert_eq!(err.path.to_string(), "/a/1");
+}
+
+#[test]
+fn test_stream_elements_at_pointer() {
+    let input = r#"{
+        "meta": {"count": 3, "tags": ["a", "b"]},
+        "data": {"skip": [[1], {"x": null}], "items": [{"id": 1}, 2, "three"], "after": true}
+    }"#;
+
+    let values: Vec<String> = Parser::new(input)
+        .into_elements_at("/data/items")
+        .map(|v| v.unwrap().to_string())
+        .collect();
+    assert_eq!(values, [r#"{"id": 1}"#, "2", r#""three""#]);
+
+    let values: Vec<String> = Parser::new(input)
+        .into_elements_at("/meta/tags")
+        .map(|v| v.unwrap().to_string())
+        .collect();
+    assert_eq!(values, [r#""a""#, r#""b""#]);
+
+    let values: Vec<String> = Parser::new(input)
+        .into_elements_at("/data/skip/0")
+        .map(|v| v.unwrap().to_string())
+        .collect();
+    assert_eq!(values, ["1"]);
+}
+
+#[test]
+fn test_stream_elements_at_pointer_escapes() {
+    let input = r#"{"a/b": {"m~n": [true]}, "": [null]}"#;
+    let values: Vec<String> = Parser::new(input)
+        .into_elements_at("/a~1b/m~0n")
+        .map(|v| v.unwrap().to_string())
+        .collect();
+    assert_eq!(values, ["true"]);
+
+    let values: Vec<String> = Parser::new(input)
+        .into_elements_at("/")
+        .map(|v| v.unwrap().to_string())
+        .collect();
+    assert_eq!(values, ["null"]);
+}
+
+#[test]
+fn test_stream_elements_at_pointer_errors() {
+    let input = r#"{"data": {"items": [1, 2 3]}, "other": []}"#;
+    let first_error = |input: &str, pointer: &str| {
+        Parser::new(input)
+            .into_elements_at(pointer)
+            .find_map(Result::err)
+            .unwrap()
+    };
+
+    let err = first_error(input, "/data/items");
+    assert_eq!(err.kind, ParseErrorKind::MissingComma);
+    assert_eq!(err.path.to_string(), "/data/items");
+
+    for pointer in [
+        "/data/missing",
+        "/nope",
+        "/data/items/x",
+        "/other/0",
+        "/data/items/5",
+    ] {
+        let err = first_error(r#"{"data": {"items": [1, 2]}, "other": []}"#, pointer);
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::PointerNotFound(pointer.to_string()),
+            "Failed for pointer: {}",
+            pointer
+        );
+    }
+
+    for pointer in ["data", "/a~2"] {
+        let err = first_error(input, pointer);
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::InvalidPointer(pointer.to_string())
+        );
+        assert_eq!((err.span.line, err.span.column), (1, 1));
+        assert!(err.to_string().ends_with("at line 1, column 1"));
+    }
+
+    // the rest of the document is still checked after the array ends
+    let err = first_error(r#"{"items": [1], "after": tru}"#, "/items");
+    assert_eq!(err.kind, ParseErrorKind::InvalidKeyword("tru".to_string()));
+    let err = first_error(r#"{"items": [1]} []"#, "/items");
+    assert_eq!(err.kind, ParseErrorKind::TrailingContent);
+}
+
+/// hands out its input a few bytes at a time, like a slow socket
+struct Trickle<'a> {
+    input: &'a [u8],
+    step: usize,
+}
+
+impl std::io::Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.step.min(buf.len()).min(self.input.len());
+        buf[..n].copy_from_slice(&self.input[..n]);
+        self.input = &self.input[n..];
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_parse_from_reader() {
+    let input = r#"{"name": "Zoë", "tags": ["日本", "🦀"], "n": -1.5e3, "ok": true}"#;
+    let expected = Parser::new(input).parse().unwrap();
+
+    let value = Parser::from_reader(input.as_bytes()).parse().unwrap();
+    assert_eq!(value, expected);
+
+    // multi-byte characters split across reads
+    for step in 1..5 {
+        let reader = Trickle {
+            input: input.as_bytes(),
+            step,
+        };
+        let value = Parser::from_reader(reader).parse().unwrap();
+        assert_eq!(value, expected, "Failed for step: {}", step);
+    }
+
+    // larger than the read buffer
+    let input = format!("[{}\"é\"]", "\"é\", ".repeat(5000));
+    let values: Vec<_> = Parser::from_reader(input.as_bytes())
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(values.len(), 5001);
+    assert!(values.iter().all(|v| *v == JsonValue::String("é".into())));
+}
+
+#[test]
+fn test_parse_from_reader_and_slice_errors() {
+    let cases: [(&[u8], ParseErrorKind, usize); 4] = [
+        (b"[\"a\xffb\"]", ParseErrorKind::InvalidUtf8, 3),
+        (b"[1, \xc3", ParseErrorKind::InvalidUtf8, 4),
+        (b"\"\xed\xa0\x80\"", ParseErrorKind::InvalidUtf8, 1),
+        (b"[1, 2", ParseErrorKind::UnexpectedEof, 5),
+    ];
+    for (input, kind, offset) in cases {
+        let err = Parser::from_reader(input).parse().err().unwrap();
+        assert_eq!(err.kind, kind, "Failed for input: {:?}", input);
+        assert_eq!(err.span.start, offset, "Failed for input: {:?}", input);
+
+        let err = Parser::from_slice(input).parse().err().unwrap();
+        assert_eq!(err.kind, kind, "Failed for input: {:?}", input);
+        assert_eq!(err.span.start, offset, "Failed for input: {:?}", input);
+    }
+
+    struct Broken;
+    impl std::io::Read for Broken {
+        fn read(&mut self, _: &mut [u8]) -> std::io::Result<usize> {
+            Err(std::io::Error::other("connection reset"))
+        }
+    }
+    let err = Parser::from_reader(Broken).parse().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::Io("connection reset".to_string()));
+
+    // a reader failing inside a character or an escape
+    let inputs: [&[u8]; 3] = [b"[\"\xc3", b"[\"\xe6\x97", b"[\"\\u00"];
+    for input in inputs {
+        let err = Parser::from_reader(FailsAfter { input })
+            .parse()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::Io("connection reset".to_string()),
+            "Failed for input: {:?}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_parse_borrowed() {
+    let input = r#"{"id": 7, "name": "plain", "note": "tab\there", "id": [1.5, null]}"#;
+    let value = Parser::new(input).parse_borrowed().unwrap();
+
+    let JsonValueRef::Object(members) = &value else {
+        panic!("expected an object, got {:?}", value);
+    };
+    let keys: Vec<_> = members.iter().map(|(k, _)| k.as_ref()).collect();
+    assert_eq!(keys, ["id", "name", "note", "id"]);
+    assert!(matches!(
+        value.get("name"),
+        Some(JsonValueRef::String(Cow::Borrowed("plain")))
+    ));
+    assert!(
+        matches!(value.get("note"), Some(JsonValueRef::String(Cow::Owned(s))) if s == "tab\there")
+    );
+    assert_eq!(
+        value.get("id"),
+        Some(&JsonValueRef::Array(vec![
+            JsonValueRef::Number("1.5".into()),
+            JsonValueRef::Null
+        ]))
+    );
+
+    let expected = Parser::new(input).parse().unwrap();
+    assert_eq!(value.into_owned(), expected);
+
+    let err = Parser::new(input)
+        .with_duplicate_key_policy(DuplicateKeyPolicy::Error)
+        .parse_borrowed()
+        .err()
+        .unwrap();
+    assert!(matches!(err.kind, ParseErrorKind::DuplicateKey { .. }));
+
+    let err = Parser::new("[1, 2] 3").parse_borrowed().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::TrailingContent);
+}
+
+#[test]
+fn test_max_depth() {
+    let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+    assert!(Parser::new(&nested(128)).parse().is_ok());
+    let err = Parser::new(&nested(129)).parse().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::DepthLimitExceeded(128));
+    assert_eq!(err.span.start, 128);
+    assert_eq!(err.path.0.len(), 128);
+
+    // hostile input fails fast instead of overflowing the stack
+    let err = Parser::new(&nested(100_000)).parse().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::DepthLimitExceeded(128));
+
+    let input = r#"{"a": {"b": [1]}, "c": 2}"#;
+    let err = Parser::new(input)
+        .with_max_depth(Some(2))
+        .parse()
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, ParseErrorKind::DepthLimitExceeded(2));
+    assert_eq!(err.path.to_string(), "/a/b");
+    assert!(Parser::new(input).with_max_depth(Some(3)).parse().is_ok());
+
+    // without a limit, anything that does not build a tree handles any depth
+    struct Nothing;
+    impl JsonVisitor for Nothing {}
+    let completed = Parser::new(&nested(100_000))
+        .with_max_depth(None)
+        .visit(&mut Nothing)
+        .unwrap();
+    assert!(completed);
+}
+
+#[test]
+fn test_limits() {
+    let input = r#"{"name": "abcdef", "ids": [1, 22, 333], "n": -1.25e10}"#;
+    let none = Limits::default();
+    let cases = [
+        (
+            Limits {
+                max_input_bytes: Some(20),
+                ..none
+            },
+            ParseErrorKind::InputTooLarge(20),
+        ),
+        (
+            Limits {
+                max_string_bytes: Some(5),
+                ..none
+            },
+            ParseErrorKind::StringTooLong(5),
+        ),
+        (
+            Limits {
+                max_number_digits: Some(4),
+                ..none
+            },
+            ParseErrorKind::NumberTooLong(4),
+        ),
+        (
+            Limits {
+                max_object_keys: Some(2),
+                ..none
+            },
+            ParseErrorKind::TooManyKeys(2),
+        ),
+        (
+            Limits {
+                max_array_elements: Some(2),
+                ..none
+            },
+            ParseErrorKind::TooManyElements(2),
+        ),
+        (
+            Limits {
+                max_nodes: Some(5),
+                ..none
+            },
+            ParseErrorKind::TooManyNodes(5),
+        ),
+        (
+            Limits {
+                max_depth: Some(1),
+                ..none
+            },
+            ParseErrorKind::DepthLimitExceeded(1),
+        ),
+    ];
+    for (limits, kind) in cases {
+        let err = Parser::new(input)
+            .with_limits(limits)
+            .parse()
+            .err()
+            .unwrap();
+        assert_eq!(err.kind, kind, "Failed for limits: {:?}", limits);
+
+        let err = Parser::from_reader(input.as_bytes())
+            .with_limits(limits)
+            .parse()
+            .err()
+            .unwrap();
+        assert_eq!(err.kind, kind, "Failed for limits: {:?}", limits);
+    }
+
+    // every limit is inclusive
+    let exact = Limits {
+        max_input_bytes: Some(input.len()),
+        max_string_bytes: Some(6),
+        max_number_digits: Some(5),
+        max_object_keys: Some(3),
+        max_array_elements: Some(3),
+        max_nodes: Some(7),
+        max_depth: Some(2),
+    };
+    assert!(Parser::new(input).with_limits(exact).parse().is_ok());
+
+    // a huge string is rejected before it is read whole
+    let err = Parser::new("\"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")
+        .with_limits(Limits {
+            max_string_bytes: Some(8),
+            ..none
+        })
+        .parse()
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, ParseErrorKind::StringTooLong(8));
+
+    // hostile numbers and words are neither buffered nor reported whole
+    let huge = |prefix: &'static str, fill: u8| {
+        prefix.as_bytes().chain(std::io::repeat(fill).take(1 << 20))
+    };
+    let limits = Limits {
+        max_number_digits: Some(10),
+        max_string_bytes: Some(10),
+        ..none
+    };
+    for fill in [b'e', b'-', b'.', b'1'] {
+        let err = Parser::from_reader(huge("[1", fill))
+            .with_limits(limits)
+            .parse()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::NumberTooLong(10),
+            "Failed for input: {}",
+            fill as char
+        );
+    }
+    let err = Parser::from_reader(huge("[1", b'e')).parse().err().unwrap();
+    let expected = format!("1{}…", "e".repeat(63));
+    assert_eq!(err.kind, ParseErrorKind::InvalidNumber(expected.clone()));
+    let input = format!("[1{}]", "e".repeat(100));
+    let err = Parser::new(&input).parse().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::InvalidNumber(expected));
+
+    let err = Parser::from_reader(huge("[1, ", b'a'))
+        .with_limits(limits)
+        .parse()
+        .err()
+        .unwrap();
+    let expected = format!("{}…", "a".repeat(64));
+    assert_eq!(err.kind, ParseErrorKind::InvalidKeyword(expected));
+
+    // a keyword cut short by the source is not an invalid keyword
+    let err = Parser::new("[true]")
+        .with_limits(Limits {
+            max_input_bytes: Some(3),
+            ..none
+        })
+        .parse()
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, ParseErrorKind::InputTooLarge(3));
+
+    let reader = FailsAfter { input: b"[tr" };
+    let err = Parser::from_reader(reader).parse().err().unwrap();
+    assert_eq!(err.kind, ParseErrorKind::Io("connection reset".to_string()));
+
+    // and neither is an escape or a character
+    for (input, max) in [(r#"["\u0041"]"#, 4), (r#"["é"]"#, 3)] {
+        let err = Parser::new(input)
+            .with_limits(Limits {
+                max_input_bytes: Some(max),
+                ..none
+            })
+            .parse()
+            .err()
+            .unwrap();
+        assert_eq!(
+            err.kind,
+            ParseErrorKind::InputTooLarge(max),
+            "Failed for input: {}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_accessors() {
+    let input =
+        r#"{"name": "Kelly", "age": 25, "admin": false, "tags": ["a", "b"], "address": null}"#;
+    let value = Parser::new(input).parse().unwrap();
+
+    assert_eq!(value.get("name").and_then(JsonValue::as_str), Some("Kelly"));
+    assert_eq!(value.get("admin").and_then(JsonValue::as_bool), Some(false));
+    assert_eq!(
+        value.get("age").and_then(JsonValue::as_number),
+        Some(&"25".parse().unwrap())
+    );
+    assert_eq!(
+        value.get("tags").and_then(|t| t.get_index(1)),
+        Some(&JsonValue::String("b".to_string()))
+    );
+    assert_eq!(
+        value
+            .get("tags")
+            .and_then(JsonValue::as_array)
+            .map(<[_]>::len),
+        Some(2)
+    );
+    assert_eq!(value.as_object().map(|map| map.len()), Some(5));
+    assert!(value.get("address").unwrap().is_null());
+    assert_eq!(value.get("missing"), None);
+    assert_eq!(value.get_index(0), None);
+    assert_eq!(value["name"].as_bool(), None);
+
+    let cases = [
+        ("{}", "object"),
+        ("[]", "array"),
+        ("\"\"", "string"),
+        ("1.5", "number"),
+        ("true", "boolean"),
+        ("null", "null"),
+    ];
+    for (input, name) in cases {
+        let value = Parser::new(input).parse().unwrap();
+        assert_eq!(value.type_name(), name, "Failed for input: {}", input);
+    }
+}
+
+#[test]
+fn test_index() {
+    let input = r#"{"address": {"city": "New York", "zip": "10001"}, "items": [1, {"id": 2}]}"#;
+    let mut value = Parser::new(input).parse().unwrap();
+
+    assert_eq!(value["address"]["city"].as_str(), Some("New York"));
+    assert_eq!(value["items"][1]["id"], JsonValue::Number(2.into()));
+    assert!(value["address"]["country"]["code"].is_null());
+    assert!(value["items"][7].is_null());
+    assert!(value["address"][0].is_null());
+
+    value["address"]["city"] = JsonValue::String("Boston".to_string());
+    value["address"]["zone"] = JsonValue::String("EST".to_string());
+    value["items"][0] = JsonValue::Boolean(true);
+    value["meta"]["version"] = JsonValue::Number(3.into());
+    assert_eq!(
+        value.to_string(),
+        r#"{"address": {"city": "Boston", "zip": "10001", "zone": "EST"}, "items": [true, {"id": 2}], "meta": {"version": 3}}"#
+    );
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn test_index_mut_out_of_bounds() {
+    let mut value = Parser::new("[1, 2]").parse().unwrap();
+    value[2] = JsonValue::Null;
+}
+
+#[test]
+fn test_pointer() {
+    let input = r#"{"a/b": [10, {"~k": "deep"}], "": 0}"#;
+    let value = Parser::new(input).parse().unwrap();
+
+    let cases = [
+        ("", Some(value.clone())),
+        ("/a~1b/0", Some(JsonValue::Number(10.into()))),
+        ("/a~1b/1/~0k", Some(JsonValue::String("deep".to_string()))),
+        ("/", Some(JsonValue::Number(0.into()))),
+        ("/a~1b/01", None),
+        ("/a~1b/2", None),
+        ("/a~1b/0/x", None),
+        ("/missing", None),
+        ("a~1b", None),
+        ("/a~2b", None),
+    ];
+    for (pointer, expected) in cases {
+        assert_eq!(
+            value.pointer(pointer),
+            expected.as_ref(),
+            "Failed for input: {}",
+            pointer
+        );
+    }
+}
+
+#[test]
+fn test_take_and_replace() {
+    let mut value = Parser::new(r#"{"flag": true, "list": [1, 2]}"#)
+        .parse()
+        .unwrap();
+
+    let list = value["list"].take();
+    assert_eq!(list.as_array().map(<[_]>::len), Some(2));
+    assert!(value["list"].is_null());
+
+    let old = value["flag"].replace(JsonValue::Boolean(false));
+    assert_eq!(old, JsonValue::Boolean(true));
+    assert_eq!(value.to_string(), r#"{"flag": false, "list": null}"#);
+}
+
+#[test]
+fn test_editing() {
+    let mut value =
+        Parser::new(r#"{"name": "app", "servers": [{"host": "a", "port": 80}], "debug": true}"#)
+            .parse()
+            .unwrap();
+
+    *value.pointer_mut("/servers/0/port").unwrap() = JsonValue::Number(8080.into());
+    value["servers"].push(Parser::new(r#"{"host": "b"}"#).parse().unwrap());
+    value
+        .pointer_mut("/servers/1")
+        .unwrap()
+        .insert("port", JsonValue::Null);
+    assert_eq!(value.remove("debug"), Some(JsonValue::Boolean(true)));
+    assert_eq!(value.remove("debug"), None);
+    value
+        .entry("tags")
+        .or_insert_with(JsonValue::default)
+        .push(JsonValue::String("tls".to_string()));
+    if let Some(name) = value.get_mut("name") {
+        *name = JsonValue::String("web".to_string());
+    }
+    if let Some(server) = value["servers"].get_index_mut(1) {
+        server.retain(|key, _| key != "port");
+        server.insert("port", JsonValue::Number(81.into()));
+    }
+
+    assert_eq!(
+        value.to_string(),
+        r#"{"name": "web", "servers": [{"host": "a", "port": 8080}, {"host": "b", "port": 81}], "tags": ["tls"]}"#
+    );
+    assert_eq!(value.pointer_mut("/servers/2"), None);
+    assert_eq!(value.pointer_mut("/name/0"), None);
+
+    assert_eq!(
+        value.keys().collect::<Vec<_>>(),
+        ["name", "servers", "tags"]
+    );
+    assert_eq!(value["servers"].values().count(), 2);
+    let (key, first) = value.entries().next().unwrap();
+    assert_eq!((key, first.as_str()), ("name", Some("web")));
+    assert_eq!(JsonValue::Null.keys().count(), 0);
+}
+
+#[test]
+fn test_sort_keys_is_recursive() {
+    let mut value =
+        Parser::new(r#"{"b": [{"y": 1, "x": 2}], "a": {"d": null, "c": {"f": 0, "e": 0}}}"#)
+            .parse()
+            .unwrap();
+    value.sort_keys();
+    assert_eq!(
+        value.to_string(),
+        r#"{"a": {"c": {"e": 0, "f": 0}, "d": null}, "b": [{"x": 2, "y": 1}]}"#
+    );
+}
@@ -0,0 +1,124 @@
+use rijson::{
+    error::ParseErrorKind,
+    parser::Parser,
+    visitor::{JsonVisitor, VisitControl},
+};
+
+/// Counts the values of a document and collects the `level` fields of its
+/// log entries, skipping every `payload`.
+#[derive(Default)]
+struct LogStats {
+    keys: usize,
+    numbers: usize,
+    strings: Vec<String>,
+    depth: usize,
+    next_is_level: bool,
+}
+
+impl JsonVisitor for LogStats {
+    fn on_object_start(&mut self) -> VisitControl {
+        self.depth += 1;
+        VisitControl::Continue
+    }
+
+    fn on_object_end(&mut self) -> VisitControl {
+        self.depth -= 1;
+        VisitControl::Continue
+    }
+
+    fn on_key(&mut self, key: &str) -> VisitControl {
+        self.keys += 1;
+        self.next_is_level = key == "level";
+        if key == "payload" {
+            VisitControl::Skip
+        } else {
+            VisitControl::Continue
+        }
+    }
+
+    fn on_string(&mut self, value: &str) -> VisitControl {
+        if self.next_is_level {
+            self.strings.push(value.to_string());
+        }
+        VisitControl::Continue
+    }
+
+    fn on_number(&mut self, _value: &str) -> VisitControl {
+        self.numbers += 1;
+        VisitControl::Continue
+    }
+}
+
+#[test]
+fn test_visit_with_skipped_keys() {
+    let input = r#"[
+        {"level": "info", "ts": 1, "payload": {"level": "hidden", "n": [1, 2, 3]}},
+        {"level": "error", "ts": 2, "payload": "x"}
+    ]"#;
+    let mut stats = LogStats::default();
+    let completed = Parser::new(input).visit(&mut stats).unwrap();
+
+    assert!(completed);
+    assert_eq!(stats.keys, 6);
+    assert_eq!(stats.numbers, 2);
+    assert_eq!(stats.strings, ["info", "error"]);
+    assert_eq!(stats.depth, 0);
+}
+
+/// Skips every array and stops at the first `null`.
+#[derive(Default)]
+struct UntilNull {
+    seen: Vec<String>,
+}
+
+impl JsonVisitor for UntilNull {
+    fn on_array_start(&mut self) -> VisitControl {
+        VisitControl::Skip
+    }
+
+    fn on_array_end(&mut self) -> VisitControl {
+        panic!("skipped arrays have no end callback");
+    }
+
+    fn on_key(&mut self, key: &str) -> VisitControl {
+        self.seen.push(key.to_string());
+        VisitControl::Continue
+    }
+
+    fn on_null(&mut self) -> VisitControl {
+        VisitControl::Abort
+    }
+}
+
+#[test]
+fn test_visit_skip_and_abort() {
+    let input = r#"{"a": [1, [null]], "b": null, "c": 3} trailing garbage"#;
+    let mut visitor = UntilNull::default();
+    let completed = Parser::new(input).visit(&mut visitor).unwrap();
+
+    assert!(!completed);
+    assert_eq!(visitor.seen, ["a", "b"]);
+}
+
+#[test]
+fn test_visit_reports_errors() {
+    struct Nothing;
+    impl JsonVisitor for Nothing {}
+
+    let cases = [
+        (r#"{"a": [1, 2,]}"#, ParseErrorKind::TrailingComma),
+        (r#"{"a": 1} 2"#, ParseErrorKind::TrailingContent),
+        (r#"{"payload": {"x" 1}}"#, ParseErrorKind::MissingColon),
+    ];
+    for (input, kind) in cases {
+        let err = Parser::new(input).visit(&mut Nothing).err().unwrap();
+        assert_eq!(err.kind, kind, "Failed for input: {}", input);
+    }
+
+    // skipped values are still validated
+    let err = Parser::new(r#"{"payload": {"x" 1}}"#)
+        .visit(&mut LogStats::default())
+        .err()
+        .unwrap();
+    assert_eq!(err.kind, ParseErrorKind::MissingColon);
+}